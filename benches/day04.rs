@@ -0,0 +1,47 @@
+use std::hint::black_box;
+
+use aoc_2023::day04::{parse_card, Card};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn vec_contains(winning_numbers: &[u32], numbers: &[u32]) -> usize {
+    numbers
+        .iter()
+        .filter(|n| winning_numbers.contains(n))
+        .count()
+}
+
+fn bench_matching(c: &mut Criterion) {
+    let input = include_str!("../src/bin/inputs/day04");
+    let parsed = input
+        .lines()
+        .map(|line| parse_card(line).expect("Could not parse card").1)
+        .collect::<Vec<_>>();
+    let cards = input
+        .lines()
+        .map(|line| Card::parse(line).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("day04 matching");
+    group.bench_function("vec contains", |b| {
+        b.iter(|| {
+            parsed
+                .iter()
+                .map(|(_, winning_numbers, numbers)| {
+                    vec_contains(black_box(winning_numbers), black_box(numbers))
+                })
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("bitset popcount", |b| {
+        b.iter(|| {
+            cards
+                .iter()
+                .map(|card| black_box(card).n_winners())
+                .sum::<u32>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_matching);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+use std::hint::black_box;
+
+use aoc_2023::day07::{parse_hands, Ruleset};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+fn bench_sort(c: &mut Criterion) {
+    let input = include_str!("../src/bin/inputs/day07");
+    let hands = parse_hands(input, &Ruleset::jokers_wild());
+
+    let mut group = c.benchmark_group("day07 sort");
+    group.bench_function("Ord", |b| {
+        b.iter_batched(
+            || hands.clone(),
+            |mut hands| {
+                hands.sort_unstable();
+                black_box(hands)
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("integer key", |b| {
+        b.iter_batched(
+            || hands.clone(),
+            |mut hands| {
+                hands.sort_unstable_by_key(|h| h.key);
+                black_box(hands)
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_sort);
+criterion_main!(benches);
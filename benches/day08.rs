@@ -0,0 +1,40 @@
+use std::hint::black_box;
+
+use aoc_2023::day08::{Graph, Network};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bench_cycles(c: &mut Criterion) {
+    let input = include_str!("../src/bin/inputs/day08");
+    let network = Network::parse(input).unwrap();
+    let graph = Graph::new(&network);
+    let starts = network
+        .nodes
+        .keys()
+        .filter(|name| name.ends_with('A'))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("day08 cycles");
+    group.sample_size(10);
+    group.bench_function("BTreeMap by name", |b| {
+        b.iter(|| {
+            for start in &starts {
+                black_box(network.cycle(start, |name| name.ends_with('Z')));
+            }
+        })
+    });
+    group.bench_function("interned graph", |b| {
+        b.iter(|| {
+            for start in &starts {
+                let start = graph.index(start).unwrap();
+                black_box(graph.cycle(start, |name| name.ends_with('Z')));
+            }
+        })
+    });
+    group.bench_function("compile graph", |b| {
+        b.iter(|| black_box(Graph::new(&network)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_cycles);
+criterion_main!(benches);
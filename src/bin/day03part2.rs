@@ -1,76 +1,20 @@
-use nom::bytes::complete::{take_till, take_while};
-use nom::character::complete::anychar;
-use nom::character::is_digit;
-use nom::error::{Error, ErrorKind};
-use nom::multi::many0;
-use nom::Err;
-use nom::IResult;
+use aoc_2023::day03::{parse_schematic, EnginePart, EnginePartNumber};
 
 fn main() {
     let input = include_str!("./inputs/day03");
-    let res = solve(input);
-    dbg!(res);
-}
-
-// ===================
-// PARSERS
-// ===================
-
-fn next_engine_part_number(line: &str) -> IResult<&str, (usize, &str)> {
-    if line.is_empty() {
-        return Err(Err::Error(Error::new(line, ErrorKind::Complete)));
-    }
-    let (line, x) = take_till(|c: char| is_digit(c as u8))(line)?;
-    let (line, part_number) = take_while(|c: char| is_digit(c as u8))(line)?;
-    Ok((line, (x.len(), part_number)))
-}
-
-fn engine_part_number_parser(line: &str, line_number: usize) -> Vec<EnginePartNumber> {
-    let (_, entries) =
-        many0(next_engine_part_number)(line).expect("Could not parse engine part numbers");
-    let mut current_x = 0;
-    let mut res = Vec::new();
-    for entry in entries {
-        if !entry.1.is_empty() {
-            current_x += entry.0;
-            res.push(EnginePartNumber::new(
-                current_x as i64,
-                line_number as i64,
-                entry.1,
-            ));
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
         }
-        current_x += entry.1.len();
-    }
-    res
-}
-
-fn is_not_engine_part(c: char) -> bool {
-    is_digit(c as u8) || c == '.'
-}
-
-fn next_engine_part(line: &str) -> IResult<&str, (usize, char)> {
-    if line.is_empty() {
-        return Err(Err::Error(Error::new(line, ErrorKind::Complete)));
-    }
-    let (line, x) = take_while(is_not_engine_part)(line)?;
-    let (line, symbol) = anychar(line)?;
-    Ok((line, (x.len(), symbol)))
-}
-
-fn engine_part_parser(line: &str, line_number: usize) -> Vec<EnginePart> {
-    let (_, entries) = many0(next_engine_part)(line).expect("Could not parse engine parsts");
-    let mut current_x = 0;
-    let mut res = Vec::new();
-    for entry in entries {
-        current_x += entry.0;
-        res.push(EnginePart::new(
-            current_x as i64,
-            line_number as i64,
-            entry.1,
-        ));
-        current_x += 1;
+        ["signed"] => {
+            let res = sum_gear_ratios(input, true);
+            dbg!(res);
+        }
+        _ => panic!("Usage: day03part2 [signed]"),
     }
-    res
 }
 
 // ===================
@@ -87,7 +31,7 @@ fn is_near(part: &EnginePart, number: &EnginePartNumber) -> bool {
     }
 }
 
-fn gear_ratio(part: &EnginePart, numbers: &[EnginePartNumber]) -> u32 {
+fn gear_ratio(part: &EnginePart, numbers: &[EnginePartNumber]) -> i64 {
     let ratios: Vec<&EnginePartNumber> = numbers.iter().filter(|n| is_near(part, n)).collect();
     if ratios.len() == 2 {
         ratios.iter().map(|r| r.value).product()
@@ -96,13 +40,12 @@ fn gear_ratio(part: &EnginePart, numbers: &[EnginePartNumber]) -> u32 {
     }
 }
 
-fn solve(input: &str) -> u32 {
-    let mut engine_part_numbers = Vec::new();
-    let mut engine_parts = Vec::new();
-    for (y, line) in input.lines().enumerate() {
-        engine_part_numbers.extend(engine_part_number_parser(line, y));
-        engine_parts.extend(engine_part_parser(line, y));
-    }
+fn solve(input: &str) -> i64 {
+    sum_gear_ratios(input, false)
+}
+
+fn sum_gear_ratios(input: &str, signed_numbers: bool) -> i64 {
+    let (engine_part_numbers, engine_parts) = parse_schematic(input, signed_numbers);
     engine_parts
         .iter()
         .filter(|p| p.value == '*')
@@ -110,44 +53,6 @@ fn solve(input: &str) -> u32 {
         .sum()
 }
 
-// ===================
-// DATA MODEL
-// ===================
-
-#[derive(Debug)]
-pub struct EnginePart {
-    pub x: i64,
-    pub y: i64,
-    pub value: char,
-}
-
-impl EnginePart {
-    pub fn new(x: i64, y: i64, value: char) -> Self {
-        EnginePart { x, y, value }
-    }
-}
-
-#[derive(Debug, Default)]
-pub struct EnginePartNumber {
-    pub x_min: i64,
-    pub x_max: i64,
-    pub y: i64,
-    pub value: u32,
-}
-
-impl EnginePartNumber {
-    pub fn new(x: i64, y: i64, value: &str) -> Self {
-        let width = value.len() as i64;
-        let value = value.parse::<u32>().expect("Value is not an uint32");
-        EnginePartNumber {
-            x_min: x,
-            x_max: x + width - 1,
-            y,
-            value,
-        }
-    }
-}
-
 // ===================
 // TESTS
 // ===================
@@ -189,4 +94,37 @@ mod test {
         let res = solve(input);
         assert_eq!(res, 6756);
     }
+
+    #[test]
+    fn test_grid_edges() {
+        let input = "2.......3
+*.......*
+4.......5";
+        let res = solve(input);
+        assert_eq!(res, 23);
+    }
+
+    #[test]
+    fn test_no_wrap_around() {
+        let input = ".......2
+*......*
+3.......";
+        let res = solve(input);
+        assert_eq!(res, 0);
+    }
+
+    #[test]
+    fn test_unicode_symbols() {
+        // U+0131 truncates to b'1', it must not be read as a digit
+        assert_eq!(solve("2*\u{131}1"), 0);
+        // Columns are counted in chars, not bytes
+        assert_eq!(solve("\u{221a}\u{221a}2*3"), 6);
+    }
+
+    #[test]
+    fn test_signed_numbers() {
+        let input = "-3*4";
+        assert_eq!(sum_gear_ratios(input, false), 12);
+        assert_eq!(sum_gear_ratios(input, true), -12);
+    }
 }
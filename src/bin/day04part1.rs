@@ -1,8 +1,4 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space0, space1};
-use nom::multi::many0;
-use nom::sequence::separated_pair;
-use nom::IResult;
+use aoc_2023::day04::Card;
 
 fn main() {
     let input = include_str!("./inputs/day04");
@@ -10,41 +6,11 @@ fn main() {
     dbg!(res);
 }
 
-fn parse_card_number(line: &str) -> IResult<&str, u32> {
-    let (line, _) = tag("Card")(line)?;
-    let (line, _) = space1(line)?;
-    let (line, card) = digit1(line)?;
-    let card = card.parse::<u32>().expect("Could not cast to u32");
-    let (line, _) = space0(line)?;
-    Ok((line, card))
-}
-
-fn parse_single_number(line: &str) -> IResult<&str, u32> {
-    let (line, _) = space0(line)?;
-    let (line, number) = digit1(line)?;
-    let number = number.parse::<u32>().expect("Could not cast to u32");
-    let (line, _) = space0(line)?;
-    Ok((line, number))
-}
-
-fn parse_numbers(line: &str) -> IResult<&str, Vec<u32>> {
-    many0(parse_single_number)(line)
-}
-
 fn process_card(line: &str) -> u32 {
-    let (_, (_, (winning_numbers, numbers))) = separated_pair(
-        parse_card_number,
-        tag(":"),
-        separated_pair(parse_numbers, tag("|"), parse_numbers),
-    )(line)
-    .expect("Could not parse card");
-
-    let n_winners = numbers
-        .iter()
-        .filter(|n| winning_numbers.contains(n))
-        .count();
+    let card = Card::parse(line).unwrap_or_else(|e| panic!("{}", e));
+    let n_winners = card.n_winners();
     if n_winners > 0 {
-        2_u32.pow(n_winners as u32 - 1)
+        2_u32.pow(n_winners - 1)
     } else {
         0
     }
@@ -1,64 +1,174 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space0, space1};
-use nom::multi::many0;
-use nom::sequence::separated_pair;
-use nom::IResult;
+use std::collections::BTreeMap;
+
+use aoc_2023::day04::{validate, Card, CardError};
 
 fn main() {
     let input = include_str!("./inputs/day04");
-    let res = solve(input);
-    dbg!(res);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
+        }
+        ["explain"] => print!("{}", clamped(input).explain()),
+        ["dag"] => {
+            for (from, to, copies) in clamped(input).edges() {
+                println!("Card {} -> Card {}: {}", from + 1, to + 1, copies);
+            }
+        }
+        ["what-if", card, extra] => {
+            let card = card.parse::<u32>().expect("Card is not a u32");
+            let extra = extra.parse::<usize>().expect("Extra wins is not a usize");
+            let cascade = clamped(input);
+            let what_if = cascade
+                .with_extra_wins(card, extra)
+                .unwrap_or_else(|e| panic!("{}", e));
+            println!(
+                "Card {} with {} more wins: {} cards instead of {}",
+                card,
+                extra,
+                what_if.total(),
+                cascade.total()
+            );
+        }
+        ["check"] => {
+            match input
+                .lines()
+                .map(Card::parse)
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(cards) => {
+                    for error in validate(&cards) {
+                        println!("{}", error);
+                    }
+                    if let Err(error) = simulate(input, OverflowPolicy::Reject) {
+                        println!("{}", error);
+                    }
+                }
+                Err(error) => println!("{}", error),
+            }
+        }
+        _ => panic!("Usage: day04part2 [explain | dag | what-if <card> <extra wins> | check]"),
+    }
 }
 
-fn parse_card_number(line: &str) -> IResult<&str, u32> {
-    let (line, _) = tag("Card")(line)?;
-    let (line, _) = space1(line)?;
-    let (line, card) = digit1(line)?;
-    let card = card.parse::<u32>().expect("Could not cast to u32");
-    let (line, _) = space0(line)?;
-    Ok((line, card))
+/// What to do with wins that would copy cards past the end of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverflowPolicy {
+    /// Only copy the cards that exist.
+    Clamp,
+    /// Fail with `CardError::WinsOverflow`.
+    Reject,
 }
 
-fn parse_single_number(line: &str) -> IResult<&str, u32> {
-    let (line, _) = space0(line)?;
-    let (line, number) = digit1(line)?;
-    let number = number.parse::<u32>().expect("Could not cast to u32");
-    let (line, _) = space0(line)?;
-    Ok((line, number))
+/// Scratchcard copies won by each card, kept per originating card so the total can be explained.
+#[derive(Debug, Clone)]
+struct Cascade {
+    pub policy: OverflowPolicy,
+    pub winnings: Vec<usize>,
+    pub amounts: Vec<u64>,
+    pub provenance: Vec<BTreeMap<usize, u64>>,
 }
 
-fn parse_numbers(line: &str) -> IResult<&str, Vec<u32>> {
-    many0(parse_single_number)(line)
-}
+impl Cascade {
+    fn simulate(winnings: Vec<usize>, policy: OverflowPolicy) -> Result<Self, CardError> {
+        let mut amounts = vec![1_u64; winnings.len()];
+        let mut provenance = vec![BTreeMap::new(); winnings.len()];
+        for (card, n_winnings) in winnings.iter().enumerate() {
+            let remaining = winnings.len() - card - 1;
+            if *n_winnings > remaining && policy == OverflowPolicy::Reject {
+                return Err(CardError::WinsOverflow {
+                    card: card as u32 + 1,
+                    wins: *n_winnings,
+                    remaining,
+                });
+            }
+            let current_card_amount = amounts[card];
+            for won in card + 1..card + n_winnings.min(&remaining) + 1 {
+                amounts[won] = amounts[won]
+                    .checked_add(current_card_amount)
+                    .ok_or(CardError::TooManyCopies)?;
+                *provenance[won].entry(card).or_insert(0) += current_card_amount;
+            }
+        }
+        amounts
+            .iter()
+            .try_fold(0_u64, |acc, n| acc.checked_add(*n))
+            .ok_or(CardError::TooManyCopies)?;
+        Ok(Cascade {
+            policy,
+            winnings,
+            amounts,
+            provenance,
+        })
+    }
 
-fn process_card(line: &str) -> usize {
-    let (_, (_, (winning_numbers, numbers))) = separated_pair(
-        parse_card_number,
-        tag(":"),
-        separated_pair(parse_numbers, tag("|"), parse_numbers),
-    )(line)
-    .expect("Could not parse card");
-
-    let n_winners = numbers
-        .iter()
-        .filter(|n| winning_numbers.contains(n))
-        .count();
-    n_winners
-}
+    fn total(&self) -> u64 {
+        self.amounts.iter().sum()
+    }
 
-fn solve(input: &str) -> u32 {
-    let input_size = input.lines().count();
-    let mut card_amount = vec![1_u32; input_size];
-    for (card, line) in input.lines().enumerate() {
-        let n_winnings = process_card(line);
-        let current_card_amount = card_amount[card];
-        if n_winnings > 0 {
-            for c in card_amount[card + 1..card + n_winnings + 1].as_mut() {
-                *c += current_card_amount;
+    fn explain(&self) -> String {
+        let mut res = String::new();
+        for (card, sources) in self.provenance.iter().enumerate() {
+            res.push_str(&format!(
+                "Card {} ({} wins): {} = 1 original",
+                card + 1,
+                self.winnings[card],
+                self.amounts[card]
+            ));
+            for (from, copies) in sources {
+                res.push_str(&format!(" + {} from card {}", copies, from + 1));
             }
+            res.push('\n');
         }
+        res.push_str(&format!("Total: {}\n", self.total()));
+        res
+    }
+
+    /// Edges of the cascade DAG as `(from, to, copies)`, always pointing to a later card.
+    fn edges(&self) -> Vec<(usize, usize, u64)> {
+        self.provenance
+            .iter()
+            .enumerate()
+            .flat_map(|(to, sources)| sources.iter().map(move |(from, n)| (*from, to, *n)))
+            .collect()
+    }
+
+    /// Replays the cascade as if card number `card` had `extra` more winning numbers.
+    fn with_extra_wins(&self, card: u32, extra: usize) -> Result<Self, CardError> {
+        let mut winnings = self.winnings.clone();
+        let wins = (card as usize)
+            .checked_sub(1)
+            .and_then(|index| winnings.get_mut(index))
+            .ok_or(CardError::UnknownCard {
+                card,
+                cards: self.winnings.len(),
+            })?;
+        *wins = wins.saturating_add(extra);
+        Cascade::simulate(winnings, self.policy)
+    }
+}
+
+/// Cards must be numbered sequentially so that a card number is also its position in the table.
+fn simulate(input: &str, policy: OverflowPolicy) -> Result<Cascade, CardError> {
+    let cards = input
+        .lines()
+        .map(Card::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(error) = validate(&cards).into_iter().find(CardError::is_fatal) {
+        return Err(error);
     }
-    card_amount.iter().sum()
+    let winnings = cards.iter().map(|c| c.n_winners() as usize).collect();
+    Cascade::simulate(winnings, policy)
+}
+
+fn clamped(input: &str) -> Cascade {
+    simulate(input, OverflowPolicy::Clamp).expect("Invalid scratchcards")
+}
+
+fn solve(input: &str) -> u64 {
+    clamped(input).total()
 }
 
 #[cfg(test)]
@@ -76,4 +186,85 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         let res = solve(input);
         assert_eq!(res, 30);
     }
+
+    #[test]
+    fn test_provenance() {
+        let input = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+        let cascade = clamped(input);
+        assert_eq!(cascade.amounts, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(
+            cascade.provenance[3],
+            BTreeMap::from([(0, 1), (1, 2), (2, 4)])
+        );
+        assert!(cascade.edges().iter().all(|(from, to, _)| from < to));
+        assert_eq!(
+            cascade.edges().iter().map(|(_, _, n)| n).sum::<u64>() + 6,
+            cascade.total()
+        );
+
+        let what_if = cascade.with_extra_wins(5, 1).unwrap();
+        assert_eq!(what_if.amounts[5], 15);
+        assert_eq!(what_if.total(), 44);
+
+        let explanation = cascade.explain();
+        assert!(explanation.contains(
+            "Card 4 (1 wins): 8 = 1 original + 1 from card 1 + 2 from card 2 + 4 from card 3\n"
+        ));
+        assert!(explanation.ends_with("Total: 30\n"));
+    }
+
+    #[test]
+    fn test_overflow_policy() {
+        let input = "Card 1: 1 2 | 3 4
+Card 2: 1 2 3 | 1 2 3";
+        assert_eq!(solve(input), 2);
+        assert_eq!(
+            simulate(input, OverflowPolicy::Reject).unwrap_err(),
+            CardError::WinsOverflow {
+                card: 2,
+                wins: 3,
+                remaining: 0
+            }
+        );
+        let cascade = clamped(input);
+        assert_eq!(cascade.with_extra_wins(1, 3).unwrap().total(), 3);
+        let cascade = simulate("Card 1: 1 | 2", OverflowPolicy::Reject).unwrap();
+        assert!(cascade.with_extra_wins(1, 1).is_err());
+        for card in [0, 2] {
+            assert_eq!(
+                cascade.with_extra_wins(card, 1).unwrap_err(),
+                CardError::UnknownCard { card, cards: 1 }
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_sequential() {
+        let input = "Card 1: 1 2 | 3 4
+Card 3: 1 2 | 1 1";
+        assert_eq!(
+            simulate(input, OverflowPolicy::Clamp).unwrap_err(),
+            CardError::NonSequential {
+                expected: 2,
+                found: 3
+            }
+        );
+        assert!(simulate("Card 1: 1 1 | 2", OverflowPolicy::Reject).is_ok());
+    }
+    #[test]
+    fn test_copies_overflow() {
+        // Each card wins every following card, so card n ends up with 2^(n-1) copies.
+        let winnings = (0..65).rev().collect::<Vec<_>>();
+        let cascade = Cascade::simulate(winnings[1..].to_vec(), OverflowPolicy::Reject).unwrap();
+        assert_eq!(cascade.total(), u64::MAX);
+        assert_eq!(
+            Cascade::simulate(winnings, OverflowPolicy::Reject).unwrap_err(),
+            CardError::TooManyCopies
+        );
+    }
 }
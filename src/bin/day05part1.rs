@@ -1,11 +1,9 @@
-use std::collections::HashMap;
-
+use aoc_2023::day05::{find_chain, parse_maps, AlmanacMap};
 use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, digit1, space0, space1};
-use nom::combinator::map_res;
+use nom::character::complete::{digit1, space0};
 use nom::multi::many0;
 use nom::sequence::tuple;
-use nom::{IResult, Parser};
+use nom::IResult;
 
 fn main() {
     let input = include_str!("./inputs/day05");
@@ -26,128 +24,43 @@ fn parse_seeds(line: &str) -> IResult<&str, Vec<Seed>> {
     Ok((i, seeds))
 }
 
-fn parse_map_name(line: &str) -> IResult<&str, (&str, &str)> {
-    tuple((alpha1, tag("-to-"), alpha1))
-        .map(|(source, _, dest)| (source, dest))
-        .parse(line)
-}
-
-fn parse_map_content(line: &str) -> IResult<&str, (u64, u64, u64)> {
-    tuple((
-        map_res(digit1, str::parse::<u64>),
-        space1,
-        map_res(digit1, str::parse::<u64>),
-        space1,
-        map_res(digit1, str::parse::<u64>),
-    ))
-    .map(|(dest, _, source, _, range)| (dest, source, range))
-    .parse(line)
-}
-
-#[derive(Debug)]
-struct AlmanacRange {
-    pub source_start: u64,
-    pub destination_start: u64,
-    pub range: u64,
-}
-
-impl AlmanacRange {
-    fn new(source_start: u64, destination_start: u64, range: u64) -> Self {
-        AlmanacRange {
-            source_start,
-            destination_start,
-            range,
-        }
-    }
-}
-
-#[derive(Debug)]
-struct AlmanacMap {
-    pub source: String,
-    pub _destination: String,
-    pub ranges: Vec<AlmanacRange>,
-}
-
-impl AlmanacMap {
-    fn new(source: &str, dest: &str) -> Self {
-        AlmanacMap {
-            source: source.into(),
-            _destination: dest.into(),
-            ranges: Vec::new(),
-        }
-    }
-}
-
 #[derive(Debug, Default)]
 struct Seed {
     pub seed: u64,
-    pub soil: u64,
-    pub fertilizer: u64,
-    pub water: u64,
-    pub light: u64,
-    pub temperature: u64,
-    pub humidity: u64,
-    pub location: u64,
+    /// Value of the seed in each category of the chain, in order.
+    pub categories: Vec<(String, u64)>,
 }
 
-fn convert(value: u64, map: &AlmanacMap) -> u64 {
-    if let Some(range) = map
-        .ranges
-        .iter()
-        .find(|r| value >= r.source_start && (value <= r.source_start + r.range))
-    {
-        range.destination_start + (value - range.source_start)
-    } else {
-        value
+impl Seed {
+    fn populate(&mut self, chain: &[&AlmanacMap]) {
+        let mut value = self.seed;
+        self.categories = chain
+            .iter()
+            .map(|map| {
+                value = map.convert(value);
+                (map.destination.clone(), value)
+            })
+            .collect();
     }
-}
 
-impl Seed {
-    fn populate(&mut self, maps: &HashMap<String, AlmanacMap>) {
-        self.soil = convert(self.seed, maps.get("seed").expect("Seed map not found"));
-        self.fertilizer = convert(self.soil, maps.get("soil").expect("Soil map not found"));
-        self.water = convert(
-            self.fertilizer,
-            maps.get("fertilizer").expect("Fertilizer map not found"),
-        );
-        self.light = convert(self.water, maps.get("water").expect("Water map not found"));
-        self.temperature = convert(self.light, maps.get("light").expect("Light map not found"));
-        self.humidity = convert(
-            self.temperature,
-            maps.get("temperature").expect("Temperature map not found"),
-        );
-        self.location = convert(
-            self.humidity,
-            maps.get("humidity").expect("Humidity map not found"),
-        );
+    fn location(&self) -> u64 {
+        self.categories
+            .last()
+            .map_or(self.seed, |(_, value)| *value)
     }
 }
 
 fn solve(input: &str) -> u64 {
-    let mut seeds: Vec<Seed> = Vec::new();
-    let mut maps: Vec<AlmanacMap> = Vec::new();
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
-        } else if let Ok((_, (dest, source, range))) = parse_map_content(line) {
-            maps.last_mut()
-                .expect("No map yet !")
-                .ranges
-                .push(AlmanacRange::new(source, dest, range))
-        } else if let Ok((_, (source, dest))) = parse_map_name(line) {
-            maps.push(AlmanacMap::new(source, dest));
-        } else if let Ok((_, seeds_parsed)) = parse_seeds(line) {
-            seeds = seeds_parsed;
-        }
-    }
-    let maps = maps
-        .into_iter()
-        .map(|map| (map.source.clone(), map))
-        .collect::<HashMap<String, AlmanacMap>>();
-    seeds.iter_mut().for_each(|s| s.populate(&maps));
+    let mut seeds = input
+        .lines()
+        .find_map(|line| parse_seeds(line).ok())
+        .map_or_else(Vec::new, |(_, seeds)| seeds);
+    let maps = parse_maps(input);
+    let chain = find_chain(&maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+    seeds.iter_mut().for_each(|s| s.populate(&chain));
     let res = seeds
         .iter()
-        .map(|s| s.location)
+        .map(|s| s.location())
         .min()
         .expect("seed list is empty");
     dbg!(seeds);
@@ -197,4 +110,32 @@ humidity-to-location map:
         let res = solve(input);
         assert_eq!(res, 35);
     }
+
+    #[test]
+    fn test_extra_categories() {
+        let input = "seeds: 1 5
+
+seed-to-colour map:
+100 0 10
+
+seed-to-dirt map:
+20 0 2
+
+dirt-to-location map:
+7 21 1";
+        let res = solve(input);
+        assert_eq!(res, 5);
+        let res = solve(&input.replace("seeds: 1 5", "seeds: 1"));
+        assert_eq!(res, 7);
+    }
+
+    #[test]
+    fn test_range_end_is_excluded() {
+        let input = "seeds: 10
+
+seed-to-location map:
+0 5 5";
+        let res = solve(input);
+        assert_eq!(res, 10);
+    }
 }
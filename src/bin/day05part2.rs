@@ -1,16 +1,83 @@
-use std::collections::HashMap;
+use std::thread;
 
+use aoc_2023::day05::{
+    find_chain, flatten, min_location_brute_force, min_location_parallel, parse_maps,
+    synthetic_almanac, to_dot, to_json, trace, AlmanacMap, Range,
+};
 use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, digit1, space0, space1};
-use nom::combinator::map_res;
+use nom::character::complete::{digit1, space0};
 use nom::multi::many0;
 use nom::sequence::tuple;
-use nom::{IResult, Parser};
+use nom::IResult;
 
 fn main() {
     let input = include_str!("./inputs/day05");
-    let res = solve(input);
-    dbg!(res);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
+        }
+        ["flatten", args @ ..] => {
+            let (source, destination) = match args {
+                [] => ("seed", "location"),
+                [source, destination] => (*source, *destination),
+                _ => panic!("Usage: day05part2 flatten [<source> <destination>]"),
+            };
+            let (_, maps) = parse_almanac(input);
+            match flatten(&maps, source, destination) {
+                Ok(map) => print!("{}", map),
+                Err(e) => println!("{}", e),
+            }
+        }
+        ["reverse", start, stop] => {
+            let start = start.parse::<u64>().expect("Start is not a u64");
+            let stop = stop.parse::<u64>().expect("Stop is not a u64");
+            let (seeds, maps) = parse_almanac(input);
+            let flat = flatten(&maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+            for r in seeds_landing_in(&seeds, &flat, start..stop) {
+                println!("{}..={} -> {}", r.start, r.stop, flat.convert(r.start));
+            }
+        }
+        ["parallel"] => {
+            let (seeds, maps) = parse_almanac(input);
+            let flat = flatten(&maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+            let res = min_location_parallel(&seeds, &flat).expect("seed list is empty");
+            dbg!(res);
+        }
+        ["brute-force"] => {
+            let (seeds, maps) = parse_almanac(input);
+            check_against_brute_force(&seeds, &maps);
+        }
+        ["synthetic", random_seed, n_seeds] => {
+            let random_seed = random_seed.parse::<u64>().expect("Seed is not a u64");
+            let n_seeds = n_seeds.parse::<u64>().expect("Seed count is not a u64");
+            let (seeds, maps) = synthetic_almanac(random_seed, 7, 100, 1 << 32, n_seeds);
+            check_against_brute_force(&seeds, &maps);
+        }
+        [format @ ("dot" | "json")] => {
+            let (seeds, maps) = parse_almanac(input);
+            let chain = find_chain(&maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+            let flows = trace(&seeds, &chain);
+            match *format {
+                "dot" => print!("{}", to_dot(&flows, &chain)),
+                _ => print!("{}", to_json(&flows, &chain)),
+            }
+        }
+        ["argmin"] => {
+            let (location, seeds) = min_location_seeds(input);
+            println!("Location {} comes from seeds {:?}", location, seeds);
+        }
+        _ => panic!(
+            "Usage: day05part2 [flatten [<source> <destination>] | reverse <start> <stop> | argmin \
+            | parallel | brute-force | synthetic <random seed> <seed count> | dot | json]"
+        ),
+    }
 }
 
 fn parse_seeds(line: &str) -> IResult<&str, Vec<Range>> {
@@ -29,209 +96,79 @@ fn parse_seeds(line: &str) -> IResult<&str, Vec<Range>> {
     Ok((i, seeds))
 }
 
-fn parse_map_name(line: &str) -> IResult<&str, (&str, &str)> {
-    tuple((alpha1, tag("-to-"), alpha1))
-        .map(|(source, _, dest)| (source, dest))
-        .parse(line)
+fn parse_almanac(input: &str) -> (Vec<Range>, Vec<AlmanacMap>) {
+    let seeds = input
+        .lines()
+        .find_map(|line| parse_seeds(line).ok())
+        .map_or_else(Vec::new, |(_, seeds)| seeds);
+    (seeds, parse_maps(input))
 }
 
-fn parse_map_content(line: &str) -> IResult<&str, (u64, u64, u64)> {
-    tuple((
-        map_res(digit1, str::parse::<u64>),
-        space1,
-        map_res(digit1, str::parse::<u64>),
-        space1,
-        map_res(digit1, str::parse::<u64>),
-    ))
-    .map(|(dest, _, source, _, range)| (dest, source, range))
-    .parse(line)
+fn check_against_brute_force(seeds: &[Range], maps: &[AlmanacMap]) {
+    let flat = flatten(maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+    let chain = find_chain(maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let by_range = min_location_parallel(seeds, &flat);
+    let by_seed = min_location_brute_force(seeds, &chain, threads);
+    println!("Ranges: {:?}, seeds: {:?}", by_range, by_seed);
+    assert_eq!(
+        by_range, by_seed,
+        "Range splitting disagrees with brute force"
+    );
 }
 
-#[derive(Debug)]
-struct AlmanacRange {
-    pub source_start: u64,
-    pub destination_start: u64,
-    pub range: u64,
-}
-
-impl AlmanacRange {
-    fn new(source_start: u64, destination_start: u64, range: u64) -> Self {
-        AlmanacRange {
-            source_start,
-            destination_start,
-            range,
-        }
-    }
-}
-
-#[derive(Debug)]
-struct AlmanacMap {
-    pub source: String,
-    pub _destination: String,
-    pub ranges: Vec<AlmanacRange>,
-}
-
-impl AlmanacMap {
-    fn new(source: &str, dest: &str) -> Self {
-        AlmanacMap {
-            source: source.into(),
-            _destination: dest.into(),
-            ranges: Vec::new(),
-        }
-    }
-}
-
-#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd)]
-struct Range {
-    pub start: u64,
-    pub stop: u64,
-    pub range: u64,
-}
-
-impl Range {
-    fn new(start: u64, range: u64) -> Self {
-        let stop = start + range - 1;
-        Range { start, stop, range }
-    }
-
-    fn split(&self, map: &AlmanacMap) -> Vec<Range> {
-        let mut split_values = Vec::new();
-        split_values.push(self.start);
-        for r in map.ranges.iter() {
-            let stop = r.source_start + r.range - 1;
-            if r.source_start > self.start && r.source_start < self.stop {
-                split_values.push(r.source_start);
-                split_values.push(r.source_start + 1);
+/// Parts of the seed ranges whose locations fall in `locations`.
+fn seeds_landing_in(
+    seeds: &[Range],
+    flat: &AlmanacMap,
+    locations: std::ops::Range<u64>,
+) -> Vec<Range> {
+    let preimage = flat.preimage(locations);
+    let mut res = Vec::new();
+    for seed in seeds {
+        for piece in preimage.iter() {
+            let start = seed.start.max(piece.start);
+            let stop = seed.stop.min(piece.end - 1);
+            if start <= stop {
+                res.push(Range::new(start, stop + 1 - start));
             }
-            if stop > self.start && stop < self.stop {
-                split_values.push(stop);
-                split_values.push(stop + 1);
-            }
-        }
-        split_values.push(self.stop);
-        split_values.sort();
-
-        let mut res = Vec::new();
-        let mut val_iter = split_values.iter();
-        while let Some(start) = val_iter.next() {
-            let stop = val_iter.next().expect("We expect to always have pairs");
-            let range = stop + 1 - start;
-            res.push(Range::new(*start, range));
-        }
-        res
-    }
-
-    fn convert(&self, map: &AlmanacMap) -> Self {
-        let mut res = self.to_owned();
-        if let Some(range) = map
-            .ranges
-            .iter()
-            .find(|r| self.start >= r.source_start && (self.start < r.source_start + r.range))
-        {
-            res.start = range.destination_start + (self.start - range.source_start);
-            res.stop = res.start + res.range - 1;
         }
-        res
     }
+    res.sort_by_key(|r| r.start);
+    res
+}
 
-    fn get_min_location(&self, maps: &HashMap<String, AlmanacMap>) -> u64 {
-        let seed_map = maps.get("seed").expect("Seed map not found");
-        let soil_map = maps.get("soil").expect("soil map not found");
-        let fert_map = maps.get("fertilizer").expect("fertilizer map not found");
-        let wate_map = maps.get("water").expect("water map not found");
-        let ligh_map = maps.get("light").expect("light map not found");
-        let temp_map = maps.get("temperature").expect("temperature map not found");
-        let humi_map = maps.get("humidity").expect("humidity map not found");
-
-        let ranges = self
-            .split(seed_map)
-            .iter()
-            .map(|r| r.convert(seed_map))
-            .collect::<Vec<_>>();
-
-        let ranges = ranges
-            .iter()
-            .flat_map(|r| r.split(soil_map))
-            .map(|r| r.convert(soil_map))
-            .collect::<Vec<_>>();
-
-        let ranges = ranges
-            .iter()
-            .flat_map(|r| r.split(fert_map))
-            .map(|r| r.convert(fert_map))
-            .collect::<Vec<_>>();
-
-        let ranges = ranges
-            .iter()
-            .flat_map(|r| r.split(wate_map))
-            .map(|r| r.convert(wate_map))
-            .collect::<Vec<_>>();
-
-        let ranges = ranges
-            .iter()
-            .flat_map(|r| r.split(ligh_map))
-            .map(|r| r.convert(ligh_map))
-            .collect::<Vec<_>>();
-
-        let ranges = ranges
-            .iter()
-            .flat_map(|r| r.split(temp_map))
-            .map(|r| r.convert(temp_map))
-            .collect::<Vec<_>>();
-
-        let ranges = ranges
-            .iter()
-            .flat_map(|r| r.split(humi_map))
-            .map(|r| r.convert(humi_map))
-            .collect::<Vec<_>>();
-
-        ranges
-            .iter()
-            .map(|r| r.start)
-            .min()
-            .expect("Got empty loc list")
-    }
+/// Lowest location along with every seed leading to it.
+fn min_location_seeds(input: &str) -> (u64, Vec<u64>) {
+    let (seeds, maps) = parse_almanac(input);
+    let flat = flatten(&maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+    let min = seeds
+        .iter()
+        .map(|s| s.get_min_location(&flat))
+        .min()
+        .expect("seed list is empty");
+    let min_seeds = seeds_landing_in(&seeds, &flat, min..min + 1)
+        .iter()
+        .flat_map(|r| r.start..=r.stop)
+        .collect();
+    (min, min_seeds)
 }
 
 fn solve(input: &str) -> u64 {
-    let mut seeds: Vec<Range> = Vec::new();
-    let mut maps: Vec<AlmanacMap> = Vec::new();
-    for line in input.lines() {
-        if line.is_empty() {
-            continue;
-        } else if let Ok((_, (dest, source, range))) = parse_map_content(line) {
-            maps.last_mut()
-                .expect("No map yet !")
-                .ranges
-                .push(AlmanacRange::new(source, dest, range))
-        } else if let Ok((_, (source, dest))) = parse_map_name(line) {
-            maps.push(AlmanacMap::new(source, dest));
-        } else if let Ok((_, seeds_parsed)) = parse_seeds(line) {
-            seeds = seeds_parsed;
-        }
-    }
-    let maps = maps
-        .into_iter()
-        .map(|map| (map.source.clone(), map))
-        .collect::<HashMap<String, AlmanacMap>>();
-
-    println!("Start seed populating");
-
-    let res = dbg!(seeds)
+    let (seeds, maps) = parse_almanac(input);
+    let flat = flatten(&maps, "seed", "location").unwrap_or_else(|e| panic!("{}", e));
+    seeds
         .iter()
-        .map(|s| dbg!(s.get_min_location(&maps)))
+        .map(|s| s.get_min_location(&flat))
         .min()
-        .expect("seed list is empty");
-    res
+        .expect("seed list is empty")
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    #[test]
-    fn it_works() {
-        let input = "seeds: 79 14 55 13
+    const INPUT: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -264,7 +201,22 @@ temperature-to-humidity map:
 humidity-to-location map:
 60 56 37
 56 93 4";
-        let res = solve(input);
+
+    #[test]
+    fn it_works() {
+        let res = solve(INPUT);
         assert_eq!(res, 46);
     }
+
+    #[test]
+    fn test_reverse() {
+        let (seeds, maps) = parse_almanac(INPUT);
+        let flat = flatten(&maps, "seed", "location").unwrap();
+        let landing = seeds_landing_in(&seeds, &flat, 50..60);
+        for seed in seeds.iter().flat_map(|r| r.start..=r.stop) {
+            let inside = landing.iter().any(|r| (r.start..=r.stop).contains(&seed));
+            assert_eq!(inside, (50..60).contains(&flat.convert(seed)));
+        }
+        assert_eq!(min_location_seeds(INPUT), (46, vec![82]));
+    }
 }
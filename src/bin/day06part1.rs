@@ -1,70 +1,34 @@
-use std::iter::zip;
-
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space0};
-use nom::multi::many0;
-use nom::sequence::tuple;
-use nom::IResult;
+use aoc_2023::day06::{count_winning_holds_with, Boat, BoatModel, RaceSheet};
 
 fn main() {
     let input = include_str!("./inputs/day06");
-    let res = solve(input);
-    dbg!(res);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
+        }
+        ["boat", options @ ..] => {
+            let boat = Boat::from_options(options).unwrap_or_else(|e| panic!("{}", e));
+            let res = solve_with(input, &boat);
+            dbg!(res);
+        }
+        _ => panic!("Usage: day06part1 [boat [acceleration=<u64>] [max-speed=<u64>] [drag=<f64>]]"),
+    }
 }
 
-fn parse_races_duration(line: &str) -> IResult<&str, Vec<u32>> {
-    let (i, _) = tuple((tag("Time:"), space0))(line)?;
-    let (i, durations) = many0(tuple((space0, digit1, space0)))(i)?;
-    let durations = durations
-        .iter()
-        .map(|(_, duration, _)| duration.parse::<u32>().expect("Not a u32"))
-        .collect();
-
-    Ok((i, durations))
+fn solve(input: &str) -> u64 {
+    solve_with(input, &Boat::default())
 }
 
-fn parse_races_distances(line: &str) -> IResult<&str, Vec<u32>> {
-    let (i, _) = tuple((tag("Distance:"), space0))(line)?;
-    let (i, distances) = many0(tuple((space0, digit1, space0)))(i)?;
-    let distances = distances
+fn solve_with(input: &str, boat: &impl BoatModel) -> u64 {
+    let sheet = RaceSheet::parse(input).unwrap_or_else(|e| panic!("{}", e));
+    sheet
+        .races()
         .iter()
-        .map(|(_, distance, _)| distance.parse::<u32>().expect("Not a u32"))
-        .collect();
-
-    Ok((i, distances))
-}
-
-fn roots(duration: f64, distance: f64) -> (f64, f64) {
-    let delta = duration.powf(2.0) - 4.0 * distance;
-    let root1 = (duration + delta.sqrt()) / 2.0;
-    let root2 = (duration - delta.sqrt()) / 2.0;
-    (root2, root1)
-}
-
-fn compute_distance(push_duration: f64, race_duration: f64) -> f64 {
-    push_duration * (race_duration - push_duration)
-}
-
-fn solve(input: &str) -> usize {
-    let mut input = input.lines();
-    let (_, durations) = parse_races_duration(input.next().expect("Duration line not found"))
-        .expect("Could not parse durations");
-    let (_, distances) = parse_races_distances(input.next().expect("Distance line not found"))
-        .expect("Could not parse distances");
-    let mut solutions = Vec::new();
-    for (duration, distance) in zip(durations.iter(), distances.iter()) {
-        let (t_min, t_max) = roots(*duration as f64, *distance as f64);
-        let mut t_min = t_min.ceil() as u32;
-        let mut t_max = t_max.floor() as u32;
-        if compute_distance(t_min as f64, *duration as f64) <= *distance as f64 {
-            t_min += 1;
-        }
-        if compute_distance(t_max as f64, *duration as f64) <= *distance as f64 {
-            t_max -= 1;
-        }
-        solutions.push((t_min..t_max).len() + 1);
-    }
-    solutions.iter().product()
+        .map(|race| count_winning_holds_with(boat, race.duration, race.record))
+        .product()
 }
 
 #[cfg(test)]
@@ -78,4 +42,20 @@ Distance:  9  40  200";
         let res = solve(input);
         assert_eq!(res, 288);
     }
+
+    #[test]
+    fn test_boat() {
+        let input = "Time:      7  15   30
+Distance:  9  40  200";
+        let boat = Boat {
+            acceleration: 2,
+            ..Default::default()
+        };
+        assert_eq!(solve_with(input, &boat), 6 * 12 * 23);
+        let boat = Boat {
+            max_speed: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(solve_with(input, &boat), 0);
+    }
 }
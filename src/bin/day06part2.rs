@@ -1,60 +1,31 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space0};
-use nom::multi::many0;
-use nom::sequence::tuple;
-use nom::IResult;
+use aoc_2023::day06::{count_winning_holds_with, Boat, BoatModel, RaceSheet};
 
 fn main() {
     let input = include_str!("./inputs/day06");
-    let res = solve(input);
-    dbg!(res);
-}
-
-fn parse_race_duration(line: &str) -> IResult<&str, u64> {
-    let (i, _) = tuple((tag("Time:"), space0))(line)?;
-    let (i, durations) = many0(tuple((space0, digit1, space0)))(i)?;
-    let mut duration = String::from("");
-    durations.iter().for_each(|(_, d, _)| duration.push_str(d));
-    let duration = duration.parse::<u64>().expect("Could not parse duration");
-    Ok((i, duration))
-}
-
-fn parse_race_distances(line: &str) -> IResult<&str, u64> {
-    let (i, _) = tuple((tag("Distance:"), space0))(line)?;
-    let (i, distances) = many0(tuple((space0, digit1, space0)))(i)?;
-    let mut distance = String::from("");
-    distances.iter().for_each(|(_, d, _)| distance.push_str(d));
-    let distance = distance.parse::<u64>().expect("Could not parse distance");
-    Ok((i, distance))
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
+        }
+        ["boat", options @ ..] => {
+            let boat = Boat::from_options(options).unwrap_or_else(|e| panic!("{}", e));
+            let res = solve_with(input, &boat);
+            dbg!(res);
+        }
+        _ => panic!("Usage: day06part2 [boat [acceleration=<u64>] [max-speed=<u64>] [drag=<f64>]]"),
+    }
 }
 
-fn roots(duration: f64, distance: f64) -> (f64, f64) {
-    let delta = duration.powf(2.0) - 4.0 * distance;
-    let root1 = (duration + delta.sqrt()) / 2.0;
-    let root2 = (duration - delta.sqrt()) / 2.0;
-    (root2, root1)
+fn solve(input: &str) -> u64 {
+    solve_with(input, &Boat::default())
 }
 
-fn compute_distance(push_duration: f64, race_duration: f64) -> f64 {
-    push_duration * (race_duration - push_duration)
-}
-
-fn solve(input: &str) -> usize {
-    let mut input = input.lines();
-    let (_, duration) = parse_race_duration(input.next().expect("Duration line not found"))
-        .expect("Could not parse durations");
-    let (_, distance) = parse_race_distances(input.next().expect("Distance line not found"))
-        .expect("Could not parse distances");
-    let (t_min, t_max) = roots(duration as f64, distance as f64);
-    let mut t_min = t_min.ceil() as u32;
-    let mut t_max = t_max.floor() as u32;
-    if compute_distance(t_min as f64, duration as f64) <= distance as f64 {
-        t_min += 1;
-    }
-    if compute_distance(t_max as f64, duration as f64) <= distance as f64 {
-        t_max -= 1;
-    }
-    (t_min..t_max).len() + 1
+fn solve_with(input: &str, boat: &impl BoatModel) -> u64 {
+    let sheet = RaceSheet::parse(input).unwrap_or_else(|e| panic!("{}", e));
+    let race = sheet.kerned().unwrap_or_else(|e| panic!("{}", e));
+    count_winning_holds_with(boat, race.duration, race.record)
 }
 
 #[cfg(test)]
@@ -68,4 +39,12 @@ Distance:  9  40  200";
         let res = solve(input);
         assert_eq!(res, 71503);
     }
+
+    #[test]
+    fn test_huge_race() {
+        let input = "Time:      1000000000 000000007
+Distance:  3000000000 000000000";
+        let res = solve(input);
+        assert_eq!(res, 1_000_000_000_000_000_002);
+    }
 }
@@ -1,111 +1,59 @@
-use std::collections::BTreeMap;
-
-use nom::bytes::complete::tag;
-use nom::character::complete::alpha1;
-use nom::sequence::tuple;
-use nom::IResult;
+use aoc_2023::day08::{navigate, to_dot, Graph, Network, NodePattern};
 
 fn main() {
     let input = include_str!("./inputs/day08");
-    let res = solve(input);
-    dbg!(res);
-}
-
-#[derive(Debug)]
-enum Instruction {
-    Left,
-    Right,
-}
-
-#[derive(Debug)]
-struct Node {
-    name: String,
-    left: String,
-    right: String,
-}
-
-impl Node {
-    fn new(name: &str, left: &str, right: &str) -> Self {
-        Node {
-            name: name.to_owned(),
-            left: left.to_owned(),
-            right: right.to_owned(),
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
         }
-    }
-
-    fn get_next(&self, instruction: &Instruction) -> &str {
-        match instruction {
-            Instruction::Left => &self.left,
-            Instruction::Right => &self.right,
+        ["cycles"] => print_cycles(input, "suffix:A", "suffix:Z"),
+        ["cycles", start, goal] => print_cycles(input, start, goal),
+        ["dot"] => print_dot(input, "suffix:A", "suffix:Z"),
+        ["dot", start, goal] => print_dot(input, start, goal),
+        ["walk", start, goal] => {
+            let graph = graph(input);
+            let res = navigate(&graph, &pattern(start), &pattern(goal))
+                .unwrap_or_else(|e| panic!("{}", e));
+            dbg!(res);
         }
+        _ => panic!(
+            "Usage: day08part2 [cycles [<start> <goal>]|dot [<start> <goal>]|walk <start> <goal>]"
+        ),
     }
 }
 
-fn parse_instructions(line: &str) -> Vec<Instruction> {
-    line.chars()
-        .map(|c| match c {
-            'L' => Some(Instruction::Left),
-            'R' => Some(Instruction::Right),
-            _ => None,
-        })
-        .map(|c| c.expect("Unexpected instruction"))
-        .collect()
+fn graph(input: &str) -> Graph {
+    let network = Network::parse(input).unwrap_or_else(|e| panic!("{}", e));
+    Graph::new(&network)
 }
 
-fn parse_node(line: &str) -> IResult<&str, Node> {
-    let (i, (name, _, left, _, right)) =
-        tuple((alpha1, tag(" = ("), alpha1, tag(", "), alpha1))(line)?;
-    Ok((i, Node::new(name, left, right)))
+fn pattern(spec: &str) -> NodePattern {
+    NodePattern::parse(spec).unwrap_or_else(|e| panic!("{}", e))
 }
 
-fn solve(input: &str) -> usize {
-    let mut lines = input.lines();
-    let instructions = parse_instructions(lines.next().expect("instruction line not found"));
-    lines.next();
-
-    let mut nodes = BTreeMap::new();
-    for line in lines {
-        let (_, node) = parse_node(line).expect("Could not parse node");
-        nodes.insert(node.name.to_owned(), node);
+fn print_cycles(input: &str, start: &str, goal: &str) {
+    let graph = graph(input);
+    for cycle in graph.cycles(&pattern(start), &pattern(goal)) {
+        println!(
+            "{}: tail {} cycle {} goals before the cycle {:?} goal offsets {:?}",
+            cycle.start, cycle.tail, cycle.length, cycle.tail_hits, cycle.offsets
+        );
     }
-
-    let mut res = Vec::new();
-
-    let starting_nodes = nodes
-        .values()
-        .filter(|n| n.name.ends_with('A'))
-        .collect::<Vec<_>>();
-    for starting_node in starting_nodes {
-        let mut current_node = starting_node;
-        for (index, instruction) in instructions.iter().cycle().enumerate() {
-            let next_name = current_node.get_next(instruction);
-            current_node = nodes.get(next_name).expect("Unknown node");
-
-            if next_name.ends_with('Z') {
-                dbg!(&current_node.name);
-                res.push(dbg!(index + 1));
-                break;
-            }
-        }
-    }
-    // lcm(res.iter().fold(1, |acc, l| num::integer::lcm(acc, *l))
-    lcm(&res)
 }
 
-pub fn lcm(nums: &[usize]) -> usize {
-    if nums.len() == 1 {
-        return nums[0];
-    }
-    let a = nums[0];
-    let b = lcm(&nums[1..]);
-    a * b / gcd_of_two_numbers(a, b)
+fn print_dot(input: &str, start: &str, goal: &str) {
+    let graph = graph(input);
+    print!("{}", to_dot(&graph, &pattern(start), &pattern(goal)));
 }
 
-fn gcd_of_two_numbers(a: usize, b: usize) -> usize {
-    if b == 0 {
-        return a;
-    }
-    gcd_of_two_numbers(b, a % b)
+fn solve(input: &str) -> u64 {
+    let graph = graph(input);
+    let start = NodePattern::Suffix("A".to_owned());
+    let goal = NodePattern::Suffix("Z".to_owned());
+    navigate(&graph, &start, &goal).unwrap_or_else(|e| panic!("{}", e))
 }
 
 #[cfg(test)]
@@ -136,4 +84,19 @@ ZZZ = (ZZZ, ZZZ)";
         let res = solve(input);
         assert_eq!(res, 6);
     }
+    #[test]
+    fn it_works3() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        let res = solve(input);
+        assert_eq!(res, 6);
+    }
 }
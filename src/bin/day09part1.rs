@@ -1,54 +1,45 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space0};
-use nom::multi::{many0, many1};
-use nom::sequence::tuple;
-use nom::IResult;
+use aoc_2023::day09::{difference_table, next, parse_history, HistoryError};
 
 fn main() {
     let input = include_str!("./inputs/day09");
-    let res = solve(input);
-    dbg!(res);
-}
-
-fn parse_history(line: &str) -> IResult<&str, Vec<i64>> {
-    let (i, values) = many1(tuple((space0, many0(tag("-")), digit1, space0)))(line)?;
-    let values = values
-        .iter()
-        .map(|(_, sign, value, _)| {
-            if sign.is_empty() {
-                value.parse::<i64>().expect("Could not parse i64")
-            } else {
-                -value.parse::<i64>().expect("Could not parse i64")
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
+        }
+        ["next", k] => {
+            let k = k.parse::<u32>().expect("Could not parse k");
+            let res = solve_with(input, k);
+            dbg!(res);
+        }
+        ["table", line] => {
+            let line = line.parse::<usize>().expect("Could not parse line number");
+            let line = input.lines().nth(line - 1).expect("No such line");
+            let (_, history) = parse_history(line).expect("Could not parse input line");
+            let table = difference_table(&history).unwrap_or_else(|e| panic!("{}", e));
+            for (depth, row) in table.iter().enumerate() {
+                let row = row.iter().map(|v| format!(" {:>4}", v)).collect::<String>();
+                println!("{:indent$}{}", "", row, indent = 5 * depth / 2);
             }
-        })
-        .collect::<Vec<_>>();
-
-    Ok((i, values))
-}
-
-fn extrapolate(history: &[i64]) -> i64 {
-    let mut intervals = Vec::new();
-    let mut interval = history.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
-    intervals.push(interval.clone());
-    loop {
-        interval = interval.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
-        if interval.iter().all(|v| *v == 0) {
-            break;
         }
-        intervals.push(interval.clone());
-    }
-    let mut next_interval = 0;
-    while let Some(interval) = intervals.pop() {
-        next_interval += interval.last().expect("Should not be empty");
+        _ => panic!("Usage: day09part1 [next <k>|table <line>]"),
     }
-    history.last().expect("Should not be empty") + next_interval
 }
 
 fn solve(input: &str) -> i64 {
-    let mut res = 0;
+    solve_with(input, 1)
+}
+
+/// Sum of the `k`-th next value of each history.
+fn solve_with(input: &str, k: u32) -> i64 {
+    let mut res = 0_i64;
     for line in input.lines() {
         let (_, history) = parse_history(line).expect("Could not parse input line");
-        res += dbg!(extrapolate(&history));
+        res = next(&history, k)
+            .and_then(|value| res.checked_add(value).ok_or(HistoryError::Overflow))
+            .unwrap_or_else(|e| panic!("{}", e));
     }
     res
 }
@@ -65,4 +56,12 @@ mod test {
         let res = solve(input);
         assert_eq!(res, 114);
     }
+    #[test]
+    fn test_solve_with() {
+        let input = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+        assert_eq!(solve_with(input, 0), 15 + 21 + 45);
+        assert_eq!(solve_with(input, 2), 21 + 36 + 101);
+    }
 }
@@ -1,54 +1,35 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space0};
-use nom::multi::{many0, many1};
-use nom::sequence::tuple;
-use nom::IResult;
+use aoc_2023::day09::{parse_history, previous, HistoryError};
 
 fn main() {
     let input = include_str!("./inputs/day09");
-    let res = solve(input);
-    dbg!(res);
-}
-
-fn parse_history(line: &str) -> IResult<&str, Vec<i64>> {
-    let (i, values) = many1(tuple((space0, many0(tag("-")), digit1, space0)))(line)?;
-    let values = values
-        .iter()
-        .map(|(_, sign, value, _)| {
-            if sign.is_empty() {
-                value.parse::<i64>().expect("Could not parse i64")
-            } else {
-                -value.parse::<i64>().expect("Could not parse i64")
-            }
-        })
-        .collect::<Vec<_>>();
-
-    Ok((i, values))
-}
-
-fn extrapolate(history: &[i64]) -> i64 {
-    let mut intervals = Vec::new();
-    let mut interval = history.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
-    intervals.push(interval.clone());
-    loop {
-        interval = interval.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
-        if interval.iter().all(|v| *v == 0) {
-            break;
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        [] => {
+            let res = solve(input);
+            dbg!(res);
         }
-        intervals.push(interval.clone());
-    }
-    let mut next_interval = 0;
-    while let Some(interval) = intervals.pop() {
-        next_interval = interval.first().expect("Should not be empty") - next_interval;
+        ["previous", k] => {
+            let k = k.parse::<u32>().expect("Could not parse k");
+            let res = solve_with(input, k);
+            dbg!(res);
+        }
+        _ => panic!("Usage: day09part2 [previous <k>]"),
     }
-    history.first().expect("Should not be empty") - next_interval
 }
 
 fn solve(input: &str) -> i64 {
-    let mut res = 0;
+    solve_with(input, 1)
+}
+
+/// Sum of the `k`-th previous value of each history.
+fn solve_with(input: &str, k: u32) -> i64 {
+    let mut res = 0_i64;
     for line in input.lines() {
         let (_, history) = parse_history(line).expect("Could not parse input line");
-        res += dbg!(extrapolate(&history));
+        res = previous(&history, k)
+            .and_then(|value| res.checked_add(value).ok_or(HistoryError::Overflow))
+            .unwrap_or_else(|e| panic!("{}", e));
     }
     res
 }
@@ -65,4 +46,12 @@ mod test {
         let res = solve(input);
         assert_eq!(res, 2);
     }
+    #[test]
+    fn test_solve_with() {
+        let input = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+        assert_eq!(solve_with(input, 0), 1 + 10);
+        assert_eq!(solve_with(input, 2), -6 - 4);
+    }
 }
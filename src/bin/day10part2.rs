@@ -226,7 +226,7 @@ fn solve(input: &str) -> usize {
             .filter(|p| p.get_coord().0 == start_x && p.get_coord().1 < start_y)
             .collect::<Vec<_>>();
 
-        pipe_tiles.sort_by(|a, b| a.get_coord().1.cmp(&b.get_coord().1));
+        pipe_tiles.sort_by_key(|t| t.get_coord().1);
         let mut start_pattern = None;
         let mut count = 0;
         for pipe_tile in pipe_tiles {
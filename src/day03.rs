@@ -0,0 +1,188 @@
+use nom::branch::alt;
+use nom::bytes::complete::take_while1;
+use nom::character::complete::{char, satisfy};
+use nom::combinator::{opt, recognize};
+use nom::multi::many0;
+use nom::sequence::pair;
+use nom::{IResult, Parser};
+
+/// Engine part numbers are made of ASCII digits only, other Unicode digits are symbols.
+pub fn is_digit(c: char) -> bool {
+    c.is_ascii_digit()
+}
+
+/// Blank cells separate numbers and symbols without being engine parts themselves.
+pub fn is_blank(c: char) -> bool {
+    c == '.' || c.is_whitespace()
+}
+
+/// Anything that is neither a digit nor a blank cell is a symbol, Unicode included.
+pub fn is_symbol(c: char) -> bool {
+    !is_digit(c) && !is_blank(c)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Token<'a> {
+    Blank(&'a str),
+    Number(&'a str),
+    Symbol(char),
+}
+
+fn parse_blank(line: &str) -> IResult<&str, Token<'_>> {
+    take_while1(is_blank).map(Token::Blank).parse(line)
+}
+
+fn parse_number(line: &str) -> IResult<&str, Token<'_>> {
+    take_while1(is_digit).map(Token::Number).parse(line)
+}
+
+fn parse_signed_number(line: &str) -> IResult<&str, Token<'_>> {
+    recognize(pair(opt(char('-')), take_while1(is_digit)))
+        .map(Token::Number)
+        .parse(line)
+}
+
+fn parse_symbol(line: &str) -> IResult<&str, Token<'_>> {
+    satisfy(is_symbol).map(Token::Symbol).parse(line)
+}
+
+/// When `signed_numbers` is set, a `-` directly followed by digits is a sign instead of a symbol.
+pub fn schematic_tokenizer(line: &str, signed_numbers: bool) -> Vec<Token<'_>> {
+    let (_, tokens) = if signed_numbers {
+        many0(alt((parse_blank, parse_signed_number, parse_symbol)))(line)
+    } else {
+        many0(alt((parse_blank, parse_number, parse_symbol)))(line)
+    }
+    .expect("Could not tokenize schematic line");
+    tokens
+}
+
+/// Numbers and parts of a line, with columns counted in chars.
+pub fn schematic_parser(
+    line: &str,
+    line_number: usize,
+    signed_numbers: bool,
+) -> (Vec<EnginePartNumber>, Vec<EnginePart>) {
+    let mut numbers = Vec::new();
+    let mut parts = Vec::new();
+    let mut current_x = 0;
+    for token in schematic_tokenizer(line, signed_numbers) {
+        match token {
+            Token::Blank(blank) => current_x += blank.chars().count(),
+            Token::Number(number) => {
+                numbers.push(EnginePartNumber::new(
+                    current_x as i64,
+                    line_number as i64,
+                    number,
+                ));
+                current_x += number.len();
+            }
+            Token::Symbol(symbol) => {
+                parts.push(EnginePart::new(
+                    current_x as i64,
+                    line_number as i64,
+                    symbol,
+                ));
+                current_x += 1;
+            }
+        }
+    }
+    (numbers, parts)
+}
+
+/// Numbers and parts of the whole schematic.
+pub fn parse_schematic(
+    input: &str,
+    signed_numbers: bool,
+) -> (Vec<EnginePartNumber>, Vec<EnginePart>) {
+    let mut engine_part_numbers = Vec::new();
+    let mut engine_parts = Vec::new();
+    for (y, line) in input.lines().enumerate() {
+        let (numbers, parts) = schematic_parser(line, y, signed_numbers);
+        engine_part_numbers.extend(numbers);
+        engine_parts.extend(parts);
+    }
+    (engine_part_numbers, engine_parts)
+}
+
+#[derive(Debug)]
+pub struct EnginePart {
+    pub x: i64,
+    pub y: i64,
+    pub value: char,
+}
+
+impl EnginePart {
+    pub fn new(x: i64, y: i64, value: char) -> Self {
+        EnginePart { x, y, value }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EnginePartNumber {
+    pub x_min: i64,
+    pub x_max: i64,
+    pub y: i64,
+    pub value: i64,
+}
+
+impl EnginePartNumber {
+    pub fn new(x: i64, y: i64, value: &str) -> Self {
+        let width = value.len() as i64;
+        let value = value.parse::<i64>().expect("Value is not an int64");
+        EnginePartNumber {
+            x_min: x,
+            x_max: x + width - 1,
+            y,
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tokenizer() {
+        let tokens = schematic_tokenizer("..-7\u{b0}.", true);
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Blank(".."),
+                Token::Number("-7"),
+                Token::Symbol('\u{b0}'),
+                Token::Blank(".")
+            ]
+        );
+        let tokens = schematic_tokenizer("-7\u{131}", false);
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Symbol('-'),
+                Token::Number("7"),
+                Token::Symbol('\u{131}')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parser() {
+        // Columns are counted in chars, not bytes
+        let (numbers, parts) = schematic_parser("\u{221a}\u{221a}.-12*", 3, true);
+        assert_eq!(
+            numbers
+                .iter()
+                .map(|n| (n.x_min, n.x_max, n.y, n.value))
+                .collect::<Vec<_>>(),
+            vec![(3, 5, 3, -12)]
+        );
+        assert_eq!(
+            parts
+                .iter()
+                .map(|p| (p.x, p.y, p.value))
+                .collect::<Vec<_>>(),
+            vec![(0, 3, '\u{221a}'), (1, 3, '\u{221a}'), (6, 3, '*')]
+        );
+    }
+}
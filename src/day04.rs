@@ -0,0 +1,302 @@
+use std::fmt;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, space0, space1};
+use nom::combinator::map_res;
+use nom::multi::many0;
+use nom::sequence::separated_pair;
+use nom::{IResult, Parser};
+
+pub fn parse_card_number(line: &str) -> IResult<&str, u32> {
+    let (line, _) = tag("Card")(line)?;
+    let (line, _) = space1(line)?;
+    let (line, card) = map_res(digit1, str::parse::<u32>)(line)?;
+    let (line, _) = space0(line)?;
+    Ok((line, card))
+}
+
+fn parse_single_number(line: &str) -> IResult<&str, u32> {
+    let (line, _) = space0(line)?;
+    let (line, number) = map_res(digit1, str::parse::<u32>)(line)?;
+    let (line, _) = space0(line)?;
+    Ok((line, number))
+}
+
+pub fn parse_numbers(line: &str) -> IResult<&str, Vec<u32>> {
+    many0(parse_single_number)(line)
+}
+
+/// Card number, winning numbers and held numbers, as written on the card.
+pub fn parse_card(line: &str) -> IResult<&str, (u32, Vec<u32>, Vec<u32>)> {
+    separated_pair(
+        parse_card_number,
+        tag(":"),
+        separated_pair(parse_numbers, tag("|"), parse_numbers),
+    )
+    .map(|(card, (winning_numbers, numbers))| (card, winning_numbers, numbers))
+    .parse(line)
+}
+
+/// Set of scratchcard numbers, stored as one bit per number.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NumberSet(u128);
+
+impl NumberSet {
+    pub const CAPACITY: u32 = u128::BITS;
+
+    pub fn insert(&mut self, number: u32) {
+        assert!(
+            number < Self::CAPACITY,
+            "Scratchcard numbers must be lower than {}",
+            Self::CAPACITY
+        );
+        self.0 |= 1 << number;
+    }
+
+    pub fn contains(&self, number: u32) -> bool {
+        number < Self::CAPACITY && self.0 & (1 << number) != 0
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        NumberSet(self.0 & other.0)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        NumberSet(self.0 | other.0)
+    }
+}
+
+impl NumberSet {
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..Self::CAPACITY).filter(|n| self.contains(*n))
+    }
+}
+
+impl FromIterator<u32> for NumberSet {
+    fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+        let mut set = NumberSet::default();
+        iter.into_iter().for_each(|n| set.insert(n));
+        set
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub number: u32,
+    pub winning_numbers: NumberSet,
+    pub numbers: NumberSet,
+    /// Numbers written twice on the same side of the card, they only count once.
+    pub duplicates: NumberSet,
+}
+
+fn duplicates(numbers: &[u32]) -> NumberSet {
+    let mut seen = NumberSet::default();
+    let mut duplicates = NumberSet::default();
+    for n in numbers {
+        if seen.contains(*n) {
+            duplicates.insert(*n);
+        }
+        seen.insert(*n);
+    }
+    duplicates
+}
+
+impl Card {
+    /// Panics on numbers that do not fit in a [`NumberSet`], [`Card::parse`] reports them.
+    pub fn new(number: u32, winning_numbers: &[u32], numbers: &[u32]) -> Self {
+        Card {
+            number,
+            winning_numbers: winning_numbers.iter().copied().collect(),
+            numbers: numbers.iter().copied().collect(),
+            duplicates: duplicates(winning_numbers).union(&duplicates(numbers)),
+        }
+    }
+
+    pub fn parse(line: &str) -> Result<Self, CardError> {
+        let (_, (number, winning_numbers, numbers)) =
+            parse_card(line).map_err(|_| CardError::InvalidLine(line.to_owned()))?;
+        if let Some(n) = winning_numbers
+            .iter()
+            .chain(&numbers)
+            .find(|n| **n >= NumberSet::CAPACITY)
+        {
+            return Err(CardError::NumberOutOfRange {
+                card: number,
+                number: *n,
+            });
+        }
+        Ok(Card::new(number, &winning_numbers, &numbers))
+    }
+
+    /// Held numbers written twice only match once, as the card keeps sets of numbers.
+    pub fn n_winners(&self) -> u32 {
+        self.winning_numbers.intersection(&self.numbers).len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CardError {
+    InvalidLine(String),
+    NumberOutOfRange {
+        card: u32,
+        number: u32,
+    },
+    NonSequential {
+        expected: u32,
+        found: u32,
+    },
+    DuplicatedNumber {
+        card: u32,
+        number: u32,
+    },
+    WinsOverflow {
+        card: u32,
+        wins: usize,
+        remaining: usize,
+    },
+    UnknownCard {
+        card: u32,
+        cards: usize,
+    },
+    TooManyCopies,
+}
+
+impl CardError {
+    /// Duplicated numbers are only reported, the other errors make the file unusable.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, CardError::DuplicatedNumber { .. })
+    }
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::InvalidLine(line) => write!(f, "Could not parse card {}", line),
+            CardError::NumberOutOfRange { card, number } => write!(
+                f,
+                "Card {} has number {}, numbers must be lower than {}",
+                card,
+                number,
+                NumberSet::CAPACITY
+            ),
+            CardError::NonSequential { expected, found } => {
+                write!(f, "Expected card {} but found card {}", expected, found)
+            }
+            CardError::DuplicatedNumber { card, number } => {
+                write!(f, "Card {} has number {} more than once", card, number)
+            }
+            CardError::WinsOverflow {
+                card,
+                wins,
+                remaining,
+            } => write!(
+                f,
+                "Card {} wins {} cards but only {} cards follow",
+                card, wins, remaining
+            ),
+            CardError::UnknownCard { card, cards } => {
+                write!(f, "No card {}, cards go from 1 to {}", card, cards)
+            }
+            CardError::TooManyCopies => write!(f, "Cards won do not fit in a u64"),
+        }
+    }
+}
+
+/// Checks that cards are numbered 1, 2, 3... and reports duplicated numbers.
+pub fn validate(cards: &[Card]) -> Vec<CardError> {
+    let mut errors = Vec::new();
+    for (index, card) in cards.iter().enumerate() {
+        let expected = index as u32 + 1;
+        if card.number != expected {
+            errors.push(CardError::NonSequential {
+                expected,
+                found: card.number,
+            });
+        }
+        errors.extend(
+            card.duplicates
+                .iter()
+                .map(|number| CardError::DuplicatedNumber {
+                    card: card.number,
+                    number,
+                }),
+        );
+    }
+    errors
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_card() {
+        let card = Card::parse("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").unwrap();
+        assert_eq!(card.number, 1);
+        assert!(card.winning_numbers.contains(41));
+        assert!(!card.winning_numbers.contains(6));
+        assert!(!card.numbers.contains(200));
+        assert_eq!(card.numbers.len(), 8);
+        assert_eq!(card.n_winners(), 4);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Card::parse("Card 2: 1 128 | 2"),
+            Err(CardError::NumberOutOfRange {
+                card: 2,
+                number: 128
+            })
+        );
+        assert!(Card::parse("Card 2: 1 127 | 127").is_ok());
+        assert_eq!(
+            Card::parse("Card 2: 99999999999 | 2"),
+            Err(CardError::InvalidLine("Card 2: 99999999999 | 2".to_owned()))
+        );
+        assert_eq!(
+            Card::parse("Card 99999999999: 1 | 2"),
+            Err(CardError::InvalidLine("Card 99999999999: 1 | 2".to_owned()))
+        );
+        assert!(Card::parse("Card 2 1 | 2").unwrap_err().is_fatal());
+    }
+
+    #[test]
+    fn test_duplicated_held_number() {
+        // Counted twice when the held numbers were a Vec, once now that they are a set.
+        let card = Card::parse("Card 1: 5 6 | 5 5 7").unwrap();
+        assert_eq!(card.n_winners(), 1);
+        let card = Card::parse("Card 1: 5 5 | 5 7").unwrap();
+        assert_eq!(card.n_winners(), 1);
+    }
+
+    #[test]
+    fn test_validate() {
+        let cards = [
+            Card::parse("Card 1: 1 2 2 | 3 4").unwrap(),
+            Card::parse("Card 3: 1 2 | 3 4 4 3").unwrap(),
+        ];
+        assert_eq!(cards[0].n_winners(), 0);
+        assert_eq!(
+            validate(&cards),
+            vec![
+                CardError::DuplicatedNumber { card: 1, number: 2 },
+                CardError::NonSequential {
+                    expected: 2,
+                    found: 3
+                },
+                CardError::DuplicatedNumber { card: 3, number: 3 },
+                CardError::DuplicatedNumber { card: 3, number: 4 },
+            ]
+        );
+        assert!(validate(&cards).iter().any(|e| e.is_fatal()));
+    }
+}
@@ -0,0 +1,979 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::thread;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{alpha1, digit1, space1};
+use nom::combinator::map_res;
+use nom::sequence::tuple;
+use nom::{IResult, Parser};
+
+pub fn parse_map_name(line: &str) -> IResult<&str, (&str, &str)> {
+    tuple((alpha1, tag("-to-"), alpha1))
+        .map(|(source, _, dest)| (source, dest))
+        .parse(line)
+}
+
+pub fn parse_map_content(line: &str) -> IResult<&str, (u64, u64, u64)> {
+    tuple((
+        map_res(digit1, str::parse::<u64>),
+        space1,
+        map_res(digit1, str::parse::<u64>),
+        space1,
+        map_res(digit1, str::parse::<u64>),
+    ))
+    .map(|(dest, _, source, _, range)| (dest, source, range))
+    .parse(line)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlmanacRange {
+    pub source_start: u64,
+    pub destination_start: u64,
+    pub range: u64,
+}
+
+impl AlmanacRange {
+    /// The range is cut short where its source or destination would go past `u64::MAX`.
+    pub fn new(source_start: u64, destination_start: u64, range: u64) -> Self {
+        let range = range
+            .min(u64::MAX - source_start)
+            .min(u64::MAX - destination_start);
+        AlmanacRange {
+            source_start,
+            destination_start,
+            range,
+        }
+    }
+
+    pub fn source_stop(&self) -> u64 {
+        self.source_start + self.range
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        value >= self.source_start && value < self.source_stop()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlmanacMap {
+    pub source: String,
+    pub destination: String,
+    /// Sorted by `source_start` and not overlapping, see `add_range`.
+    pub ranges: Vec<AlmanacRange>,
+}
+
+impl AlmanacMap {
+    pub fn new(source: &str, dest: &str) -> Self {
+        AlmanacMap {
+            source: source.into(),
+            destination: dest.into(),
+            ranges: Vec::new(),
+        }
+    }
+
+    pub fn add_range(&mut self, range: AlmanacRange) {
+        let index = self
+            .ranges
+            .partition_point(|r| r.source_start < range.source_start);
+        self.ranges.insert(index, range);
+    }
+
+    /// Range containing `value`, found by binary search.
+    pub fn find(&self, value: u64) -> Option<&AlmanacRange> {
+        let index = self.ranges.partition_point(|r| r.source_start <= value);
+        index
+            .checked_sub(1)
+            .map(|i| &self.ranges[i])
+            .filter(|r| r.contains(value))
+    }
+
+    pub fn convert(&self, value: u64) -> u64 {
+        match self.find(value) {
+            Some(range) => range.destination_start + (value - range.source_start),
+            None => value,
+        }
+    }
+
+    /// Ranges covering the whole `0..u64::MAX` domain, unmapped gaps become identity ranges.
+    fn segments(&self) -> Vec<AlmanacRange> {
+        let mut res = Vec::new();
+        let mut current = 0;
+        for r in self.ranges.iter() {
+            if r.source_start > current {
+                res.push(AlmanacRange::new(
+                    current,
+                    current,
+                    r.source_start - current,
+                ));
+            }
+            res.push(*r);
+            current = r.source_stop();
+        }
+        if current < u64::MAX {
+            res.push(AlmanacRange::new(current, current, u64::MAX - current));
+        }
+        res
+    }
+
+    /// Sorted source ranges whose values are converted into `values`.
+    pub fn preimage(&self, values: std::ops::Range<u64>) -> Vec<std::ops::Range<u64>> {
+        let mut pieces = self
+            .segments()
+            .iter()
+            .filter_map(|r| {
+                let start = values.start.max(r.destination_start);
+                let stop = values.end.min(r.destination_start + r.range);
+                (start < stop).then(|| {
+                    r.source_start + (start - r.destination_start)
+                        ..r.source_start + (stop - r.destination_start)
+                })
+            })
+            .collect::<Vec<_>>();
+        pieces.sort_by_key(|r| r.start);
+        let mut res: Vec<std::ops::Range<u64>> = Vec::new();
+        for piece in pieces {
+            match res.last_mut() {
+                Some(last) if last.end == piece.start => last.end = piece.end,
+                _ => res.push(piece),
+            }
+        }
+        res
+    }
+
+    /// Every source value converted into `value`, a gap and a range can both lead to it.
+    pub fn invert(&self, value: u64) -> Vec<u64> {
+        match value.checked_add(1) {
+            Some(stop) => self.preimage(value..stop).into_iter().flatten().collect(),
+            // Ranges stop short of u64::MAX, so only u64::MAX itself leads to it.
+            None => vec![value],
+        }
+    }
+
+    /// Map equivalent to applying `self` then `other`.
+    pub fn compose(&self, other: &AlmanacMap) -> AlmanacMap {
+        let mut res = AlmanacMap::new(&self.source, &other.destination);
+        let other_segments = other.segments();
+        for r in self.segments() {
+            let start = r.destination_start;
+            let stop = r.destination_start + r.range;
+            let first = other_segments.partition_point(|o| o.source_stop() <= start);
+            for o in other_segments[first..]
+                .iter()
+                .take_while(|o| o.source_start < stop)
+            {
+                let piece_start = start.max(o.source_start);
+                let piece_stop = stop.min(o.source_stop());
+                let piece = AlmanacRange::new(
+                    r.source_start + (piece_start - start),
+                    o.destination_start + (piece_start - o.source_start),
+                    piece_stop - piece_start,
+                );
+                if piece.source_start == piece.destination_start {
+                    continue;
+                }
+                match res.ranges.last_mut() {
+                    Some(last)
+                        if last.source_stop() == piece.source_start
+                            && last.destination_start + last.range == piece.destination_start =>
+                    {
+                        last.range += piece.range
+                    }
+                    _ => res.ranges.push(piece),
+                }
+            }
+        }
+        res
+    }
+}
+
+/// Inclusive range of values from the same category.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd)]
+pub struct Range {
+    pub start: u64,
+    pub stop: u64,
+    pub range: u64,
+}
+
+impl Range {
+    pub fn new(start: u64, range: u64) -> Self {
+        let stop = start + range - 1;
+        Range { start, stop, range }
+    }
+
+    /// Pieces of the range that are each converted by a single map range or gap.
+    pub fn split(&self, map: &AlmanacMap) -> Vec<Range> {
+        let mut split_values = vec![self.start];
+        for r in map.ranges.iter() {
+            for value in [r.source_start, r.source_stop()] {
+                if value > self.start && value <= self.stop {
+                    split_values.push(value);
+                }
+            }
+        }
+        split_values.sort();
+        split_values.dedup();
+        split_values.push(self.stop + 1);
+        split_values
+            .windows(2)
+            .map(|w| Range::new(w[0], w[1] - w[0]))
+            .collect()
+    }
+
+    /// Only valid on pieces coming from `split` with the same map.
+    pub fn convert(&self, map: &AlmanacMap) -> Self {
+        Range::new(map.convert(self.start), self.range)
+    }
+
+    pub fn get_min_location(&self, flat: &AlmanacMap) -> u64 {
+        self.split(flat)
+            .iter()
+            .map(|r| r.convert(flat).start)
+            .min()
+            .expect("Got empty loc list")
+    }
+}
+
+/// Same layout as the almanac so a composed map can be read like the input.
+impl fmt::Display for AlmanacMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}-to-{} map:", self.source, self.destination)?;
+        for r in self.ranges.iter() {
+            writeln!(f, "{} {} {}", r.destination_start, r.source_start, r.range)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_maps(input: &str) -> Vec<AlmanacMap> {
+    let mut maps: Vec<AlmanacMap> = Vec::new();
+    for line in input.lines() {
+        if let Ok((_, (dest, source, range))) = parse_map_content(line) {
+            maps.last_mut()
+                .expect("No map yet !")
+                .add_range(AlmanacRange::new(source, dest, range))
+        } else if let Ok((_, (source, dest))) = parse_map_name(line) {
+            maps.push(AlmanacMap::new(source, dest));
+        }
+    }
+    maps
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    UnknownCategory(String),
+    MissingLink { source: String, destination: String },
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::UnknownCategory(category) => {
+                write!(f, "No map uses the {} category", category)
+            }
+            ChainError::MissingLink {
+                source,
+                destination,
+            } => write!(
+                f,
+                "No chain of maps goes from {} to {}",
+                source, destination
+            ),
+            ChainError::Cycle(categories) => {
+                write!(f, "Maps form a cycle: {}", categories.join(" -> "))
+            }
+        }
+    }
+}
+
+fn check_category(maps: &[AlmanacMap], category: &str) -> Result<(), ChainError> {
+    if maps
+        .iter()
+        .any(|m| m.source == category || m.destination == category)
+    {
+        Ok(())
+    } else {
+        Err(ChainError::UnknownCategory(category.into()))
+    }
+}
+
+/// Depth first walk over the maps reachable from `category`, failing on the first cycle.
+fn check_cycles<'a>(
+    maps: &'a [AlmanacMap],
+    category: &'a str,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Result<(), ChainError> {
+    if let Some(index) = path.iter().position(|c| *c == category) {
+        let mut cycle = path[index..]
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        cycle.push(category.into());
+        return Err(ChainError::Cycle(cycle));
+    }
+    if done.contains(category) {
+        return Ok(());
+    }
+    path.push(category);
+    for map in maps.iter().filter(|m| m.source == category) {
+        check_cycles(maps, &map.destination, path, done)?;
+    }
+    path.pop();
+    done.insert(category);
+    Ok(())
+}
+
+/// Shortest list of maps leading from the `source` category to the `destination` one.
+pub fn find_chain<'a>(
+    maps: &'a [AlmanacMap],
+    source: &'a str,
+    destination: &str,
+) -> Result<Vec<&'a AlmanacMap>, ChainError> {
+    check_category(maps, source)?;
+    check_category(maps, destination)?;
+    check_cycles(maps, source, &mut Vec::new(), &mut HashSet::new())?;
+
+    let mut previous: HashMap<&str, &AlmanacMap> = HashMap::new();
+    let mut queue = VecDeque::from([source]);
+    while let Some(category) = queue.pop_front() {
+        if category == destination {
+            let mut chain = Vec::new();
+            let mut current = category;
+            while let Some(map) = previous.get(current) {
+                chain.push(*map);
+                current = &map.source;
+            }
+            chain.reverse();
+            return Ok(chain);
+        }
+        for map in maps.iter().filter(|m| m.source == category) {
+            if map.destination != source && !previous.contains_key(map.destination.as_str()) {
+                previous.insert(&map.destination, map);
+                queue.push_back(&map.destination);
+            }
+        }
+    }
+    Err(ChainError::MissingLink {
+        source: source.into(),
+        destination: destination.into(),
+    })
+}
+
+/// Single map going from the `source` category to the `destination` one.
+pub fn flatten(
+    maps: &[AlmanacMap],
+    source: &str,
+    destination: &str,
+) -> Result<AlmanacMap, ChainError> {
+    let chain = find_chain(maps, source, destination)?;
+    Ok(chain
+        .iter()
+        .fold(AlmanacMap::new(source, source), |acc, map| acc.compose(map)))
+}
+
+/// Lowest location of the seed ranges, each range is evaluated on its own thread. Empty ranges
+/// are skipped.
+pub fn min_location_parallel(seeds: &[Range], flat: &AlmanacMap) -> Option<u64> {
+    thread::scope(|scope| {
+        let handles = seeds
+            .iter()
+            .filter(|s| s.range > 0)
+            .map(|s| scope.spawn(|| s.get_min_location(flat)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("Seed range thread panicked"))
+            .min()
+    })
+}
+
+/// Lowest location found by converting every single seed through the chain, without any
+/// range splitting. Each non-empty seed range is cut into one chunk per thread, with at least
+/// one thread.
+pub fn min_location_brute_force(
+    seeds: &[Range],
+    chain: &[&AlmanacMap],
+    threads: usize,
+) -> Option<u64> {
+    let threads = threads.max(1);
+    let chunks = seeds
+        .iter()
+        .filter(|s| s.range > 0)
+        .flat_map(|s| {
+            let chunk_size = s.range.div_ceil(threads as u64);
+            (s.start..=s.stop)
+                .step_by(chunk_size as usize)
+                .map(move |start| start..=s.stop.min(start + chunk_size - 1))
+        })
+        .collect::<Vec<_>>();
+    thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|t| {
+                let chunks = &chunks;
+                scope.spawn(move || {
+                    chunks
+                        .iter()
+                        .skip(t)
+                        .step_by(threads)
+                        .flat_map(|chunk| chunk.clone())
+                        .map(|seed| chain.iter().fold(seed, |value, map| map.convert(value)))
+                        .min()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .filter_map(|h| h.join().expect("Brute force thread panicked"))
+            .min()
+    })
+}
+
+/// A piece of `parent`, converted by the map at `step` of the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flow {
+    pub step: usize,
+    pub parent: Range,
+    pub piece: Range,
+    pub converted: Range,
+}
+
+impl Flow {
+    pub fn shift(&self) -> i128 {
+        self.converted.start as i128 - self.piece.start as i128
+    }
+}
+
+/// Every split and shift applied to the seed ranges along the chain.
+pub fn trace(seeds: &[Range], chain: &[&AlmanacMap]) -> Vec<Flow> {
+    let mut flows = Vec::new();
+    let mut current = seeds.to_vec();
+    for (step, map) in chain.iter().enumerate() {
+        let mut next = Vec::new();
+        for parent in current {
+            for piece in parent.split(map) {
+                let converted = piece.convert(map);
+                next.push(converted.clone());
+                flows.push(Flow {
+                    step,
+                    parent: parent.clone(),
+                    piece,
+                    converted,
+                });
+            }
+        }
+        current = next;
+    }
+    flows
+}
+
+fn node_id(step: usize, range: &Range) -> String {
+    format!("n{}_{}_{}", step, range.start, range.stop)
+}
+
+fn push_dot_cluster(res: &mut String, step: usize, category: &str, mut nodes: Vec<&Range>) {
+    nodes.dedup();
+    res.push_str(&format!(
+        "    subgraph cluster_{} {{\n        label=\"{}\";\n",
+        step, category
+    ));
+    for node in nodes {
+        res.push_str(&format!(
+            "        {} [label=\"{}..={}\"];\n",
+            node_id(step, node),
+            node.start,
+            node.stop
+        ));
+    }
+    res.push_str("    }\n");
+}
+
+/// Graphviz graph with one column per category and one edge per converted piece.
+pub fn to_dot(flows: &[Flow], chain: &[&AlmanacMap]) -> String {
+    let mut res = String::from("digraph almanac {\n    rankdir=LR;\n    node [shape=box];\n");
+    for (step, map) in chain.iter().enumerate() {
+        let nodes = flows
+            .iter()
+            .filter(|f| f.step == step)
+            .map(|f| &f.parent)
+            .collect();
+        push_dot_cluster(&mut res, step, &map.source, nodes);
+    }
+    if let Some(map) = chain.last() {
+        let nodes = flows
+            .iter()
+            .filter(|f| f.step + 1 == chain.len())
+            .map(|f| &f.converted)
+            .collect();
+        push_dot_cluster(&mut res, chain.len(), &map.destination, nodes);
+    }
+    for flow in flows {
+        res.push_str(&format!(
+            "    {} -> {} [label=\"{}..={} {:+}\"];\n",
+            node_id(flow.step, &flow.parent),
+            node_id(flow.step + 1, &flow.converted),
+            flow.piece.start,
+            flow.piece.stop,
+            flow.shift()
+        ));
+    }
+    res.push_str("}\n");
+    res
+}
+
+/// Sankey friendly JSON: nodes are ranges per category, links carry the piece width.
+pub fn to_json(flows: &[Flow], chain: &[&AlmanacMap]) -> String {
+    let mut nodes: Vec<(usize, &Range)> = Vec::new();
+    for flow in flows {
+        for node in [(flow.step, &flow.parent), (flow.step + 1, &flow.converted)] {
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+    }
+    let categories = chain
+        .iter()
+        .map(|m| m.source.as_str())
+        .chain(chain.last().map(|m| m.destination.as_str()))
+        .collect::<Vec<_>>();
+    let nodes = nodes
+        .iter()
+        .map(|(step, range)| {
+            format!(
+                "{{\"id\":\"{}\",\"category\":\"{}\",\"start\":{},\"stop\":{}}}",
+                node_id(*step, range),
+                categories[*step],
+                range.start,
+                range.stop
+            )
+        })
+        .collect::<Vec<_>>();
+    let links = flows
+        .iter()
+        .map(|f| {
+            format!(
+                "{{\"source\":\"{}\",\"target\":\"{}\",\"value\":{},\"start\":{},\"stop\":{},\"shift\":{}}}",
+                node_id(f.step, &f.parent),
+                node_id(f.step + 1, &f.converted),
+                f.piece.range,
+                f.piece.start,
+                f.piece.stop,
+                f.shift()
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"categories\":[{}],\"nodes\":[{}],\"links\":[{}]}}\n",
+        categories
+            .iter()
+            .map(|c| format!("\"{}\"", c))
+            .collect::<Vec<_>>()
+            .join(","),
+        nodes.join(","),
+        links.join(",")
+    )
+}
+
+/// SplitMix64, enough randomness for synthetic almanacs without pulling a dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, max: u64) -> u64 {
+        self.next() % max
+    }
+}
+
+/// Deterministic almanac going from `seed` to `location` through `n_maps` maps, with values
+/// spread over `0..span`. Seed ranges cover about `seeds` values in total.
+pub fn synthetic_almanac(
+    random_seed: u64,
+    n_maps: usize,
+    ranges_per_map: usize,
+    span: u64,
+    seeds: u64,
+) -> (Vec<Range>, Vec<AlmanacMap>) {
+    let mut rng = SplitMix64(random_seed);
+    let mut categories = vec!["seed".to_string()];
+    categories.extend((1..n_maps).map(|i| format!("category{}", i)));
+    categories.push("location".into());
+
+    let mut maps = Vec::new();
+    for names in categories.windows(2) {
+        let mut map = AlmanacMap::new(&names[0], &names[1]);
+        let mut cuts = (0..ranges_per_map)
+            .map(|_| rng.below(span))
+            .collect::<Vec<_>>();
+        cuts.sort();
+        cuts.dedup();
+        for w in cuts.windows(2) {
+            // Leave about one gap for four ranges
+            if rng.below(4) != 0 {
+                map.add_range(AlmanacRange::new(w[0], rng.below(span), w[1] - w[0]));
+            }
+        }
+        maps.push(map);
+    }
+
+    let n_ranges = 4;
+    let seed_ranges = (0..n_ranges)
+        .map(|_| Range::new(rng.below(span), seeds / n_ranges + 1))
+        .collect();
+    (seed_ranges, maps)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const INPUT: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    #[test]
+    fn test_convert() {
+        let maps = parse_maps(INPUT);
+        assert_eq!(maps.len(), 7);
+        assert_eq!(maps[0].convert(49), 49);
+        assert_eq!(maps[0].convert(50), 52);
+        assert_eq!(maps[0].convert(97), 99);
+        assert_eq!(maps[0].convert(98), 50);
+        assert_eq!(maps[0].convert(99), 51);
+        assert_eq!(maps[0].convert(100), 100);
+    }
+
+    #[test]
+    fn test_compose() {
+        let maps = parse_maps(INPUT);
+        let composed = maps[0].compose(&maps[1]);
+        assert_eq!(composed.source, "seed");
+        assert_eq!(composed.destination, "fertilizer");
+        for seed in 0..200 {
+            assert_eq!(
+                composed.convert(seed),
+                maps[1].convert(maps[0].convert(seed))
+            );
+        }
+        assert!(composed
+            .ranges
+            .windows(2)
+            .all(|w| w[0].source_stop() <= w[1].source_start));
+    }
+
+    #[test]
+    fn test_compose_chain() {
+        let maps = parse_maps(INPUT);
+        let flat = flatten(&maps, "seed", "location").unwrap();
+        assert_eq!(flat.source, "seed");
+        assert_eq!(flat.destination, "location");
+        for seed in 0..200 {
+            let location = maps.iter().fold(seed, |value, map| map.convert(value));
+            assert_eq!(flat.convert(seed), location);
+        }
+        assert_eq!(flat.convert(79), 82);
+        assert_eq!(flat.convert(14), 43);
+        assert_eq!(flat.convert(55), 86);
+        assert_eq!(flat.convert(13), 35);
+        assert_eq!(flat.convert(u64::MAX - 1), u64::MAX - 1);
+        assert!(flat.to_string().starts_with("seed-to-location map:\n"));
+    }
+
+    #[test]
+    fn test_range_near_max() {
+        let maps = parse_maps(
+            "a-to-b map:
+18446744073709551610 0 100
+0 18446744073709551600 100
+
+b-to-c map:
+1 0 10",
+        );
+        assert_eq!(maps[0].ranges[0].range, 5);
+        assert_eq!(maps[0].ranges[1].range, 15);
+        assert_eq!(maps[0].convert(4), u64::MAX - 1);
+        assert_eq!(maps[0].convert(5), 5);
+        assert_eq!(maps[0].convert(u64::MAX - 1), 14);
+        let composed = maps[0].compose(&maps[1]);
+        for value in (0..20).chain(u64::MAX - 20..u64::MAX) {
+            assert_eq!(
+                composed.convert(value),
+                maps[1].convert(maps[0].convert(value))
+            );
+        }
+    }
+
+    #[test]
+    fn test_invert() {
+        let maps = parse_maps(INPUT);
+        assert_eq!(maps[0].invert(52), vec![50]);
+        assert_eq!(maps[0].invert(50), vec![98]);
+        // 98 itself is moved by the seed-to-soil map
+        assert_eq!(maps[0].invert(98), vec![96]);
+        assert_eq!(maps[0].invert(99), vec![97]);
+        let flat = flatten(&maps, "seed", "location").unwrap();
+        for seed in 0..200 {
+            assert!(flat.invert(flat.convert(seed)).contains(&seed));
+        }
+        assert_eq!(flat.invert(46), vec![82]);
+
+        let maps = parse_maps("a-to-b map:\n0 10 5");
+        assert_eq!(maps[0].invert(3), vec![3, 13]);
+        assert_eq!(maps[0].invert(12), vec![]);
+        assert_eq!(maps[0].preimage(5..20), vec![5..10, 15..20]);
+
+        let maps = parse_maps("a-to-b map:\n18446744073709551610 0 100");
+        assert_eq!(maps[0].invert(u64::MAX - 1), vec![4, u64::MAX - 1]);
+        assert_eq!(maps[0].invert(u64::MAX), vec![u64::MAX]);
+        assert_eq!(
+            maps[0].preimage(u64::MAX - 6..u64::MAX),
+            vec![0..5, u64::MAX - 6..u64::MAX]
+        );
+    }
+
+    #[test]
+    fn test_preimage() {
+        let maps = parse_maps(INPUT);
+        let flat = flatten(&maps, "seed", "location").unwrap();
+        for (start, stop) in [(0, 10), (40, 60), (46, 47), (55, 120), (150, 300)] {
+            let preimage = flat.preimage(start..stop);
+            assert!(preimage.windows(2).all(|w| w[0].end < w[1].start));
+            for seed in 0..400 {
+                let location = flat.convert(seed);
+                let inside = preimage.iter().any(|r| r.contains(&seed));
+                assert_eq!(inside, (start..stop).contains(&location), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_chain() {
+        let maps = parse_maps(INPUT);
+        let chain = find_chain(&maps, "seed", "location").unwrap();
+        assert_eq!(chain.len(), 7);
+        let chain = find_chain(&maps, "water", "temperature").unwrap();
+        let categories = chain.iter().map(|m| m.source.as_str()).collect::<Vec<_>>();
+        assert_eq!(categories, vec!["water", "light"]);
+        assert!(find_chain(&maps, "seed", "seed").unwrap().is_empty());
+        assert_eq!(
+            find_chain(&maps, "location", "seed").unwrap_err(),
+            ChainError::MissingLink {
+                source: "location".into(),
+                destination: "seed".into()
+            }
+        );
+        assert_eq!(
+            find_chain(&maps, "seed", "mana").unwrap_err(),
+            ChainError::UnknownCategory("mana".into())
+        );
+    }
+
+    #[test]
+    fn test_renamed_categories() {
+        let input = "seed-to-dirt map:
+50 98 2
+
+dirt-to-rock map:
+10 0 5
+
+dirt-to-spot map:
+0 0 1
+
+spot-to-place map:
+7 0 1";
+        let maps = parse_maps(input);
+        let flat = flatten(&maps, "seed", "place").unwrap();
+        assert_eq!(flat.convert(0), 7);
+        assert_eq!(flat.convert(98), 50);
+        assert_eq!(flatten(&maps, "seed", "rock").unwrap().convert(1), 11);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let input = "seed-to-soil map:
+0 1 1
+
+soil-to-water map:
+0 1 1
+
+water-to-soil map:
+0 1 1
+
+water-to-location map:
+0 1 1";
+        let maps = parse_maps(input);
+        assert_eq!(
+            find_chain(&maps, "seed", "location").unwrap_err(),
+            ChainError::Cycle(vec!["soil".into(), "water".into(), "soil".into()])
+        );
+    }
+
+    #[test]
+    fn test_parallel() {
+        let seeds = vec![Range::new(79, 14), Range::new(55, 13)];
+        let maps = parse_maps(INPUT);
+        let flat = flatten(&maps, "seed", "location").unwrap();
+        let chain = find_chain(&maps, "seed", "location").unwrap();
+        assert_eq!(min_location_parallel(&seeds, &flat), Some(46));
+        for threads in [0, 1, 3, 8] {
+            assert_eq!(min_location_brute_force(&seeds, &chain, threads), Some(46));
+        }
+
+        let seeds = vec![Range::new(5, 0), Range::new(79, 1)];
+        assert_eq!(min_location_parallel(&seeds, &flat), Some(82));
+        assert_eq!(min_location_brute_force(&seeds, &chain, 4), Some(82));
+        assert_eq!(min_location_brute_force(&seeds[..1], &chain, 4), None);
+    }
+
+    #[test]
+    fn test_synthetic() {
+        for random_seed in 0..5 {
+            let (seeds, maps) = synthetic_almanac(random_seed, 7, 20, 1_000_000, 20_000);
+            let flat = flatten(&maps, "seed", "location").unwrap();
+            let chain = find_chain(&maps, "seed", "location").unwrap();
+            assert_eq!(chain.len(), 7);
+            assert_eq!(
+                min_location_parallel(&seeds, &flat),
+                min_location_brute_force(&seeds, &chain, 4)
+            );
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let seeds = vec![Range::new(79, 14), Range::new(55, 13)];
+        let maps = parse_maps(INPUT);
+        let chain = find_chain(&maps, "seed", "location").unwrap();
+        let flows = trace(&seeds, &chain);
+        for step in 0..chain.len() {
+            let width = flows
+                .iter()
+                .filter(|f| f.step == step)
+                .map(|f| f.piece.range)
+                .sum::<u64>();
+            assert_eq!(width, 27);
+        }
+        let locations = flows
+            .iter()
+            .filter(|f| f.step == 6)
+            .map(|f| f.converted.start);
+        assert_eq!(locations.min(), Some(46));
+        assert_eq!(flows[0].shift(), 2);
+
+        let dot = to_dot(&flows, &chain);
+        assert!(dot.starts_with("digraph almanac {\n"));
+        assert!(dot.contains("    n0_79_92 -> n1_81_94 [label=\"79..=92 +2\"];\n"));
+        assert!(dot.contains("        label=\"location\";\n"));
+
+        let json = to_json(&flows, &chain);
+        assert!(json.starts_with("{\"categories\":[\"seed\",\"soil\","));
+        assert!(json.contains(
+            "{\"source\":\"n0_79_92\",\"target\":\"n1_81_94\",\"value\":14,\"start\":79,\"stop\":92,\"shift\":2}"
+        ));
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+    }
+
+    /// Random maps with non overlapping sources, destinations may overlap.
+    fn almanac_map(
+        source: &'static str,
+        destination: &'static str,
+    ) -> impl Strategy<Value = AlmanacMap> {
+        prop::collection::vec((0_u64..20, 1_u64..30, 0_u64..300), 0..6).prop_map(move |pieces| {
+            let mut map = AlmanacMap::new(source, destination);
+            let mut current = 0;
+            for (gap, range, dest) in pieces {
+                map.add_range(AlmanacRange::new(current + gap, dest, range));
+                current += gap + range;
+            }
+            map
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_convert_matches_scan(map in almanac_map("a", "b"), value in 0_u64..400) {
+            let expected = map
+                .ranges
+                .iter()
+                .find(|r| r.source_start <= value && value < r.source_start + r.range)
+                .map_or(value, |r| r.destination_start + value - r.source_start);
+            prop_assert_eq!(map.convert(value), expected);
+        }
+
+        #[test]
+        fn prop_split_matches_seeds(
+            map in almanac_map("a", "b"),
+            start in 0_u64..300,
+            range in 1_u64..60,
+        ) {
+            let pieces = Range::new(start, range).split(&map);
+            prop_assert_eq!(pieces.first().unwrap().start, start);
+            prop_assert_eq!(pieces.last().unwrap().stop, start + range - 1);
+            prop_assert!(pieces.windows(2).all(|w| w[0].stop + 1 == w[1].start));
+            for piece in pieces {
+                let converted = piece.convert(&map);
+                for seed in piece.start..=piece.stop {
+                    prop_assert_eq!(map.convert(seed), converted.start + (seed - piece.start));
+                }
+            }
+        }
+
+        #[test]
+        fn prop_ranges_match_seeds(
+            maps in (almanac_map("a", "b"), almanac_map("b", "c"), almanac_map("c", "d")),
+            seeds in prop::collection::vec((0_u64..300, 1_u64..40), 1..4),
+        ) {
+            let maps = vec![maps.0, maps.1, maps.2];
+            let flat = flatten(&maps, "a", "d").unwrap();
+            let seeds = seeds
+                .into_iter()
+                .map(|(start, range)| Range::new(start, range))
+                .collect::<Vec<_>>();
+            let by_range = seeds.iter().map(|s| s.get_min_location(&flat)).min();
+            let by_seed = seeds
+                .iter()
+                .flat_map(|s| s.start..=s.stop)
+                .map(|seed| maps.iter().fold(seed, |value, map| map.convert(value)))
+                .min();
+            prop_assert_eq!(by_range, by_seed);
+        }
+    }
+}
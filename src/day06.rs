@@ -0,0 +1,437 @@
+use std::fmt;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, space0};
+use nom::multi::many0;
+use nom::sequence::tuple;
+use nom::IResult;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Race {
+    pub duration: u64,
+    pub record: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceSheetError {
+    MissingRow(&'static str),
+    CountMismatch { times: usize, distances: usize },
+    Overflow(String),
+}
+
+impl fmt::Display for RaceSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceSheetError::MissingRow(label) => write!(f, "{} row not found", label),
+            RaceSheetError::CountMismatch { times, distances } => {
+                write!(f, "Found {} times but {} distances", times, distances)
+            }
+            RaceSheetError::Overflow(value) => write!(f, "{} does not fit in a u64", value),
+        }
+    }
+}
+
+fn parse_row<'a>(label: &'static str, line: &'a str) -> IResult<&'a str, Vec<&'a str>> {
+    let (i, _) = tuple((tag(label), space0))(line)?;
+    let (i, values) = many0(tuple((space0, digit1, space0)))(i)?;
+    Ok((i, values.iter().map(|(_, value, _)| *value).collect()))
+}
+
+fn parse_u64(value: &str) -> Result<u64, RaceSheetError> {
+    value
+        .parse::<u64>()
+        .map_err(|_| RaceSheetError::Overflow(value.into()))
+}
+
+/// Time and Distance rows, kept as written so they can be read as several races or as a single
+/// race with the spaces removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceSheet {
+    pub times: Vec<String>,
+    pub distances: Vec<String>,
+}
+
+impl RaceSheet {
+    pub fn parse(input: &str) -> Result<Self, RaceSheetError> {
+        let row = |label| {
+            input
+                .lines()
+                .find_map(|line| parse_row(label, line).ok())
+                .map(|(_, values)| values.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+                .ok_or(RaceSheetError::MissingRow(label))
+        };
+        let times = row("Time:")?;
+        let distances = row("Distance:")?;
+        if times.len() != distances.len() {
+            return Err(RaceSheetError::CountMismatch {
+                times: times.len(),
+                distances: distances.len(),
+            });
+        }
+        for value in times.iter().chain(distances.iter()) {
+            parse_u64(value)?;
+        }
+        Ok(RaceSheet { times, distances })
+    }
+
+    pub fn races(&self) -> Vec<Race> {
+        self.times
+            .iter()
+            .zip(self.distances.iter())
+            .map(|(time, distance)| Race {
+                duration: parse_u64(time).expect("Checked when parsing"),
+                record: parse_u64(distance).expect("Checked when parsing"),
+            })
+            .collect()
+    }
+
+    /// The single race read by ignoring the spaces between numbers.
+    pub fn kerned(&self) -> Result<Race, RaceSheetError> {
+        Ok(Race {
+            duration: parse_u64(&self.times.concat())?,
+            record: parse_u64(&self.distances.concat())?,
+        })
+    }
+}
+
+/// Distance travelled when holding the button `hold` ms during a `duration` ms race.
+pub fn distance(hold: u128, duration: u128) -> u128 {
+    hold * (duration - hold)
+}
+
+/// Number of hold times beating `record`, using integers only.
+///
+/// Holding `h` wins when `h * (duration - h) > record`, which holds strictly between the roots
+/// `(duration ± sqrt(duration² - 4 * record)) / 2`. The integer square root only gives a
+/// candidate for the first winning hold, it is then corrected against the exact distance.
+pub fn count_winning_holds(duration: u64, record: u64) -> u64 {
+    let (duration, record) = (duration as u128, record as u128);
+    let square = duration * duration;
+    if square <= 4 * record {
+        return 0;
+    }
+    let root = (square - 4 * record).isqrt();
+    let mut first = (duration - root.min(duration)) / 2;
+    while first <= duration / 2 && distance(first, duration) <= record {
+        first += 1;
+    }
+    while first > 0 && distance(first - 1, duration) > record {
+        first -= 1;
+    }
+    if first > duration / 2 {
+        return 0;
+    }
+    // Winning holds are symmetric around duration / 2
+    (duration - 2 * first + 1) as u64
+}
+
+pub trait BoatModel {
+    /// Distance covered during a `duration` ms race when the button is held `hold` ms.
+    fn distance(&self, hold: u64, duration: u64) -> f64;
+
+    /// Exact number of winning holds, for models where it can be computed directly.
+    fn closed_form(&self, _duration: u64, _record: u64) -> Option<u64> {
+        None
+    }
+}
+
+/// Boat gaining `acceleration` mm/ms of speed per ms held, capped at `max_speed`. Once released
+/// it loses `drag` of its speed per ms, so it covers `v * (1 - e^(-drag * t)) / drag` in `t` ms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boat {
+    pub acceleration: u64,
+    pub max_speed: Option<u64>,
+    pub drag: Option<f64>,
+}
+
+impl Default for Boat {
+    /// The puzzle boat: 1 mm/ms per ms held, no limit and no drag.
+    fn default() -> Self {
+        Boat {
+            acceleration: 1,
+            max_speed: None,
+            drag: None,
+        }
+    }
+}
+
+impl Boat {
+    /// Parses `acceleration=<u64>`, `max-speed=<u64>` and `drag=<f64>` options.
+    pub fn from_options(options: &[&str]) -> Result<Self, String> {
+        let mut boat = Boat::default();
+        for option in options {
+            match option.split_once('=') {
+                Some(("acceleration", value)) => {
+                    boat.acceleration = value.parse().map_err(|e| format!("{}: {}", option, e))?
+                }
+                Some(("max-speed", value)) => {
+                    boat.max_speed = Some(value.parse().map_err(|e| format!("{}: {}", option, e))?)
+                }
+                Some(("drag", value)) => {
+                    boat.drag = Some(value.parse().map_err(|e| format!("{}: {}", option, e))?)
+                }
+                _ => return Err(format!("Unknown boat option {}", option)),
+            }
+        }
+        Ok(boat)
+    }
+
+    pub fn speed(&self, hold: u64) -> u64 {
+        let speed = self.acceleration.saturating_mul(hold);
+        self.max_speed.map_or(speed, |max| speed.min(max))
+    }
+}
+
+impl BoatModel for Boat {
+    fn distance(&self, hold: u64, duration: u64) -> f64 {
+        let speed = self.speed(hold) as f64;
+        let time = (duration - hold) as f64;
+        match self.drag {
+            Some(drag) if drag > 0.0 => speed * (1.0 - (-drag * time).exp()) / drag,
+            _ => speed * time,
+        }
+    }
+
+    fn closed_form(&self, duration: u64, record: u64) -> Option<u64> {
+        match (self.max_speed, self.drag) {
+            // a * x > record exactly when x > record / a, for any integer x
+            (None, None) if self.acceleration > 0 => {
+                Some(count_winning_holds(duration, record / self.acceleration))
+            }
+            (None, None) => Some(0),
+            _ => None,
+        }
+    }
+}
+
+/// First hold in `low..high` for which `predicate` holds, `high` if none does. The predicate must
+/// be false then true over the range.
+fn partition_point(mut low: u64, mut high: u64, predicate: impl Fn(u64) -> bool) -> u64 {
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if predicate(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low
+}
+
+/// Counts winning holds by binary search, assuming the distance first increases then decreases
+/// with the hold time. This holds for every `Boat` as distance is then log-concave.
+pub fn search_winning_holds(model: &impl BoatModel, duration: u64, record: u64) -> u64 {
+    let record = record as f64;
+    let peak = partition_point(0, duration, |h| {
+        model.distance(h + 1, duration) <= model.distance(h, duration)
+    });
+    if model.distance(peak, duration) <= record {
+        return 0;
+    }
+    let first = partition_point(0, peak, |h| model.distance(h, duration) > record);
+    let last = partition_point(peak, duration + 1, |h| {
+        model.distance(h, duration) <= record
+    });
+    last - first
+}
+
+/// Number of hold times beating `record` with any boat model.
+pub fn count_winning_holds_with(model: &impl BoatModel, duration: u64, record: u64) -> u64 {
+    model
+        .closed_form(duration, record)
+        .unwrap_or_else(|| search_winning_holds(model, duration, record))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Previous floating point implementation, kept to show where it breaks.
+    fn count_winning_holds_f64(duration: u64, record: u64) -> u64 {
+        let (duration, record) = (duration as f64, record as f64);
+        let delta = duration.powf(2.0) - 4.0 * record;
+        let mut t_min = ((duration - delta.sqrt()) / 2.0).ceil();
+        let mut t_max = ((duration + delta.sqrt()) / 2.0).floor();
+        if t_min * (duration - t_min) <= record {
+            t_min += 1.0;
+        }
+        if t_max * (duration - t_max) <= record {
+            t_max -= 1.0;
+        }
+        (t_max - t_min) as u64 + 1
+    }
+
+    fn brute_force(duration: u64, record: u64) -> u64 {
+        (0..=duration as u128)
+            .filter(|h| distance(*h, duration as u128) > record as u128)
+            .count() as u64
+    }
+
+    #[test]
+    fn test_small_races() {
+        for duration in 0..60 {
+            for record in 0..1000 {
+                assert_eq!(
+                    count_winning_holds(duration, record),
+                    brute_force(duration, record),
+                    "duration {} record {}",
+                    duration,
+                    record
+                );
+            }
+        }
+        assert_eq!(count_winning_holds(7, 9), 4);
+        assert_eq!(count_winning_holds(15, 40), 8);
+        assert_eq!(count_winning_holds(30, 200), 9);
+        assert_eq!(count_winning_holds(71530, 940200), 71503);
+    }
+
+    #[test]
+    fn test_huge_races() {
+        // The first winning hold is 5, f64 loses it in the cancellation of duration - sqrt(delta)
+        let (duration, record) = (1 << 60, 1 << 62);
+        assert_eq!(count_winning_holds(duration, record), duration - 10 + 1);
+        assert_ne!(count_winning_holds_f64(duration, record), duration - 10 + 1);
+
+        let (duration, record) = (1_000_000_000_000_000_007, 3_000_000_000_000_000_000);
+        assert_eq!(count_winning_holds(duration, record), duration - 6 + 1);
+        assert_ne!(count_winning_holds_f64(duration, record), duration - 6 + 1);
+
+        // Record just below the best distance, only the middle hold wins
+        let duration = 1 << 32;
+        let record = (1 << 62) - 1;
+        assert_eq!(count_winning_holds(duration, record), 1);
+
+        assert_eq!(count_winning_holds(u64::MAX, 0), u64::MAX - 1);
+        assert_eq!(count_winning_holds(u64::MAX, u64::MAX), u64::MAX - 3);
+        assert_eq!(count_winning_holds(1 << 33, u64::MAX), 1);
+        assert_eq!(count_winning_holds(1 << 32, u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_race_sheet() {
+        let sheet = RaceSheet::parse("Time:      7  15   30\nDistance:  9  40  200").unwrap();
+        assert_eq!(
+            sheet.races(),
+            vec![
+                Race {
+                    duration: 7,
+                    record: 9
+                },
+                Race {
+                    duration: 15,
+                    record: 40
+                },
+                Race {
+                    duration: 30,
+                    record: 200
+                },
+            ]
+        );
+        assert_eq!(
+            sheet.kerned(),
+            Ok(Race {
+                duration: 71530,
+                record: 940200
+            })
+        );
+
+        let sheet = RaceSheet::parse("Time: 1 007\nDistance: 0 0").unwrap();
+        assert_eq!(sheet.kerned().unwrap().duration, 1007);
+    }
+
+    #[test]
+    fn test_race_sheet_errors() {
+        assert_eq!(
+            RaceSheet::parse("Time: 7 15\nDistance: 9"),
+            Err(RaceSheetError::CountMismatch {
+                times: 2,
+                distances: 1
+            })
+        );
+        assert_eq!(
+            RaceSheet::parse("Time: 7"),
+            Err(RaceSheetError::MissingRow("Distance:"))
+        );
+        assert_eq!(
+            RaceSheet::parse("Time: 18446744073709551616\nDistance: 1"),
+            Err(RaceSheetError::Overflow("18446744073709551616".into()))
+        );
+        let sheet = RaceSheet::parse("Time: 18446744073 709551616\nDistance: 1 2").unwrap();
+        assert_eq!(sheet.races().len(), 2);
+        assert_eq!(
+            sheet.kerned(),
+            Err(RaceSheetError::Overflow("18446744073709551616".into()))
+        );
+    }
+
+    #[test]
+    fn test_boat_options() {
+        assert_eq!(Boat::from_options(&[]), Ok(Boat::default()));
+        assert_eq!(
+            Boat::from_options(&["acceleration=3", "max-speed=10", "drag=0.5"]),
+            Ok(Boat {
+                acceleration: 3,
+                max_speed: Some(10),
+                drag: Some(0.5)
+            })
+        );
+        assert!(Boat::from_options(&["speed=3"]).is_err());
+        assert!(Boat::from_options(&["drag=fast"]).is_err());
+    }
+
+    #[test]
+    fn test_search_matches_closed_form() {
+        for acceleration in 1..4 {
+            let boat = Boat {
+                acceleration,
+                ..Default::default()
+            };
+            for duration in 0..40 {
+                for record in (0..1500).step_by(7) {
+                    assert_eq!(
+                        search_winning_holds(&boat, duration, record),
+                        boat.closed_form(duration, record).unwrap(),
+                    );
+                }
+            }
+        }
+        let (duration, record) = (1_000_000_000_000_000_007, 3_000_000_000_000_000_000);
+        assert_eq!(
+            count_winning_holds_with(&Boat::default(), duration, record),
+            count_winning_holds(duration, record)
+        );
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        let boats = [
+            Boat {
+                acceleration: 2,
+                max_speed: Some(9),
+                drag: None,
+            },
+            Boat {
+                acceleration: 1,
+                max_speed: None,
+                drag: Some(0.1),
+            },
+            Boat {
+                acceleration: 4,
+                max_speed: Some(30),
+                drag: Some(0.05),
+            },
+        ];
+        for boat in boats {
+            assert_eq!(boat.closed_form(10, 10), None);
+            for duration in 0..60 {
+                for record in (0..500).step_by(3) {
+                    let expected = (0..=duration)
+                        .filter(|h| boat.distance(*h, duration) > record as f64)
+                        .count() as u64;
+                    assert_eq!(count_winning_holds_with(&boat, duration, record), expected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,693 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::iter::zip;
+
+use itertools::Itertools;
+
+use nom::character::complete::{alphanumeric1, digit1, space1};
+use nom::sequence::tuple;
+use nom::IResult;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum HandType {
+    Five,
+    StraightFlush,
+    Four,
+    Full,
+    Flush,
+    Straight,
+    Three,
+    TwoPairs,
+    OnePair,
+    HighCard,
+}
+
+impl HandType {
+    /// 0 for the weakest hand type up to 9 for the strongest.
+    pub fn strength(&self) -> u64 {
+        match self {
+            HandType::HighCard => 0,
+            HandType::OnePair => 1,
+            HandType::TwoPairs => 2,
+            HandType::Three => 3,
+            HandType::Straight => 4,
+            HandType::Flush => 5,
+            HandType::Full => 6,
+            HandType::Four => 7,
+            HandType::StraightFlush => 8,
+            HandType::Five => 9,
+        }
+    }
+}
+
+impl PartialOrd for HandType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HandType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.strength().cmp(&other.strength())
+    }
+}
+
+/// How hands are read and how ties between hands of the same type are broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Evaluation {
+    /// One character per card, ties broken card by card in the order they are dealt.
+    Camel,
+    /// Two characters per card, its value then its suit as in `TH` or `2S`. Straights and
+    /// flushes count and ties are broken by kickers, from the largest group of cards.
+    Poker { suits: Vec<char> },
+}
+
+/// What a Camel Cards variant changes: the cards and their strength, the wildcards and which
+/// hand type each combination of cards makes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+    /// Cards from the weakest to the strongest.
+    pub card_order: Vec<char>,
+    /// Cards standing for whichever card makes the best hand, usually also the weakest ones.
+    pub wildcards: Vec<char>,
+    pub hand_size: usize,
+    /// Hand type for each count of identical cards, sorted from the largest count.
+    pub hand_types: Vec<(Vec<usize>, HandType)>,
+    pub evaluation: Evaluation,
+}
+
+impl Ruleset {
+    pub fn five_card_table() -> Vec<(Vec<usize>, HandType)> {
+        vec![
+            (vec![5], HandType::Five),
+            (vec![4, 1], HandType::Four),
+            (vec![3, 2], HandType::Full),
+            (vec![3, 1, 1], HandType::Three),
+            (vec![2, 2, 1], HandType::TwoPairs),
+            (vec![2, 1, 1, 1], HandType::OnePair),
+            (vec![1, 1, 1, 1, 1], HandType::HighCard),
+        ]
+    }
+
+    pub fn standard() -> Self {
+        Ruleset {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            hand_size: 5,
+            hand_types: Ruleset::five_card_table(),
+            evaluation: Evaluation::Camel,
+        }
+    }
+
+    /// Regular poker with the four suits and no wildcard.
+    pub fn poker() -> Self {
+        Ruleset {
+            evaluation: Evaluation::Poker {
+                suits: "CDHS".chars().collect(),
+            },
+            ..Ruleset::standard()
+        }
+    }
+
+    /// Jokers replace jacks, they are the weakest card but stand for any other card.
+    pub fn jokers_wild() -> Self {
+        Ruleset {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Ruleset::standard()
+        }
+    }
+
+    pub fn rank(&self, card: char) -> Option<usize> {
+        self.card_order.iter().position(|c| *c == card)
+    }
+
+    /// Hand type of the cards as they are, wildcards included.
+    fn table_hand_type(&self, cards: &[char]) -> Option<HandType> {
+        let mut map = HashMap::new();
+        cards.iter().for_each(|c| *map.entry(c).or_insert(0) += 1);
+
+        let mut counts = map.into_values().collect::<Vec<usize>>();
+        counts.sort();
+        counts.reverse();
+
+        self.hand_types
+            .iter()
+            .find(|(pattern, _)| *pattern == counts)
+            .map(|(_, hand_type)| *hand_type)
+    }
+
+    /// Best hand type reachable by replacing each wildcard with a regular card, along with the
+    /// substituted hand. Ties keep the hand with the strongest cards.
+    pub fn best_substitution(&self, value: &str) -> Option<(HandType, String)> {
+        let cards = value.chars().collect::<Vec<_>>();
+        if cards.len() != self.hand_size {
+            return None;
+        }
+        let wild = cards
+            .iter()
+            .positions(|c| self.wildcards.contains(c))
+            .collect::<Vec<_>>();
+
+        // A card missing from the hand counts the same whichever it is, so trying the strongest
+        // one is enough on top of the regular cards already held.
+        let mut candidates = cards
+            .iter()
+            .filter(|c| !self.wildcards.contains(c))
+            .copied()
+            .collect::<Vec<_>>();
+        if let Some(c) = self
+            .card_order
+            .iter()
+            .rev()
+            .find(|c| !self.wildcards.contains(c))
+        {
+            candidates.push(*c);
+        }
+        candidates.sort_by_key(|c| self.rank(*c));
+        candidates.dedup();
+
+        if wild.is_empty() || candidates.is_empty() {
+            return self
+                .table_hand_type(&cards)
+                .map(|hand_type| (hand_type, value.to_owned()));
+        }
+
+        wild.iter()
+            .map(|_| candidates.iter())
+            .multi_cartesian_product()
+            .filter_map(|substitution| {
+                let mut hand = cards.clone();
+                zip(&wild, substitution).for_each(|(i, c)| hand[*i] = *c);
+                self.table_hand_type(&hand).map(|t| (t, hand))
+            })
+            .max_by_key(|(hand_type, hand)| {
+                (
+                    *hand_type,
+                    hand.iter().map(|c| self.rank(*c)).collect::<Vec<_>>(),
+                )
+            })
+            .map(|(hand_type, hand)| (hand_type, hand.into_iter().collect()))
+    }
+
+    /// Poker hand type and kickers, strongest first. Wildcards are not supported here.
+    fn poker_hand(&self, value: &str, suits: &[char]) -> Option<(HandType, Vec<usize>)> {
+        let cards = value.chars().collect::<Vec<_>>();
+        if cards.len() != 2 * self.hand_size
+            || !cards.chunks(2).map(|c| (c[0], c[1])).all_unique()
+            || cards.chunks(2).any(|c| !suits.contains(&c[1]))
+        {
+            return None;
+        }
+        let values = cards.iter().step_by(2).copied().collect::<Vec<_>>();
+        let mut hand_type = self.table_hand_type(&values)?;
+
+        let mut kickers = values
+            .iter()
+            .map(|c| self.rank(*c))
+            .collect::<Option<Vec<_>>>()?;
+        let counts = kickers.iter().copied().counts();
+        kickers.sort_by_key(|rank| std::cmp::Reverse((counts[rank], *rank)));
+
+        let flush = cards.iter().skip(1).step_by(2).all_equal();
+        let distinct = counts.len() == self.hand_size;
+        let mut straight =
+            distinct && kickers[0] - kickers[self.hand_size - 1] == self.hand_size - 1;
+        // The ace also plays below the lowest card, as in A2345.
+        if distinct && kickers[0] == self.card_order.len() - 1 && kickers[1] == self.hand_size - 2 {
+            kickers.rotate_left(1);
+            straight = true;
+        }
+        if straight && flush {
+            hand_type = hand_type.max(HandType::StraightFlush);
+        } else if flush {
+            hand_type = hand_type.max(HandType::Flush);
+        } else if straight {
+            hand_type = hand_type.max(HandType::Straight);
+        }
+        Some((hand_type, kickers))
+    }
+
+    /// Hand type and the ranks compared in order to break ties.
+    pub fn evaluate(&self, value: &str) -> Option<(HandType, Vec<usize>)> {
+        match &self.evaluation {
+            Evaluation::Camel => {
+                let (hand_type, _) = self.best_substitution(value)?;
+                let ranks = value
+                    .chars()
+                    .map(|c| self.rank(c))
+                    .collect::<Option<Vec<_>>>()?;
+                Some((hand_type, ranks))
+            }
+            Evaluation::Poker { suits } => self.poker_hand(value, suits),
+        }
+    }
+
+    pub fn hand_type(&self, value: &str) -> Option<HandType> {
+        self.evaluate(value).map(|(hand_type, _)| hand_type)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Hand {
+    pub value: String,
+    pub bid: u32,
+    pub hand_type: HandType,
+    /// Ranks compared in order to break ties: every card as dealt, or the kickers for poker.
+    pub ranks: Vec<usize>,
+    /// Sorts like the hand: hand type strength in the high bits then 4 bits per rank.
+    pub key: u64,
+}
+
+impl Hand {
+    pub fn new(value: &str, bid: u32, rules: &Ruleset) -> Self {
+        assert!(
+            rules.card_order.len() <= 16 && rules.hand_size <= 15,
+            "Hand keys only fit 16 different cards and 15 cards per hand"
+        );
+        let (hand_type, ranks) = rules
+            .evaluate(value)
+            .expect("Could not identify the hand type");
+        let key = ranks
+            .iter()
+            .fold(hand_type.strength(), |key, rank| key << 4 | *rank as u64);
+        Hand {
+            value: value.to_owned(),
+            bid,
+            hand_type,
+            ranks,
+            key,
+        }
+    }
+}
+
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hand_type
+            .cmp(&other.hand_type)
+            .then_with(|| self.ranks.cmp(&other.ranks))
+    }
+}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub fn parse_hand<'a>(line: &'a str, rules: &Ruleset) -> IResult<&'a str, Hand> {
+    let (i, (hand, _, bid)) = tuple((alphanumeric1, space1, digit1))(line)?;
+    let bid = bid.parse::<u32>().expect("Could not parse bid");
+    Ok((i, Hand::new(hand, bid, rules)))
+}
+
+pub fn parse_hands(input: &str, rules: &Ruleset) -> Vec<Hand> {
+    input
+        .lines()
+        .map(|line| parse_hand(line, rules).expect("Could not parse hand").1)
+        .collect()
+}
+
+/// Rank of each hand of a sorted slice. Tied hands share the lowest of the ranks they cover, so
+/// the order of the input lines does not matter.
+fn shared_ranks(hands: &[Hand]) -> Vec<u32> {
+    let mut ranks: Vec<u32> = Vec::with_capacity(hands.len());
+    for (i, hand) in hands.iter().enumerate() {
+        let rank = match ranks.last() {
+            Some(last) if hands[i - 1].key == hand.key => *last,
+            _ => i as u32 + 1,
+        };
+        ranks.push(rank);
+    }
+    ranks
+}
+
+/// A hand once ranked against all the others.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Standing {
+    pub hand: Hand,
+    /// 1 for the weakest hand.
+    pub rank: u32,
+    pub winnings: u32,
+    /// Hand type of the cards as dealt, when wildcards made a better one.
+    pub upgraded_from: Option<HandType>,
+}
+
+/// Hands from the weakest to the strongest with what each one wins, tied hands in the order of
+/// the input.
+pub fn standings(input: &str, rules: &Ruleset) -> Vec<Standing> {
+    let mut hands = parse_hands(input, rules);
+    hands.sort_by_key(|h| h.key);
+    let ranks = shared_ranks(&hands);
+    hands
+        .into_iter()
+        .zip(ranks)
+        .map(|(hand, rank)| {
+            let upgraded_from = match rules.evaluation {
+                Evaluation::Camel => rules
+                    .table_hand_type(&hand.value.chars().collect::<Vec<_>>())
+                    .filter(|hand_type| *hand_type != hand.hand_type),
+                Evaluation::Poker { .. } => None,
+            };
+            Standing {
+                winnings: hand.bid * rank,
+                rank,
+                hand,
+                upgraded_from,
+            }
+        })
+        .collect()
+}
+
+/// Sum of each bid multiplied by the rank of its hand.
+pub fn total_winnings(input: &str, rules: &Ruleset) -> u32 {
+    standings(input, rules).iter().map(|s| s.winnings).sum()
+}
+
+/// Number of hands of each type, from the strongest type.
+pub fn breakdown(standings: &[Standing]) -> Vec<(HandType, usize)> {
+    let counts = standings.iter().map(|s| s.hand.hand_type).counts();
+    counts
+        .into_iter()
+        .sorted_by_key(|(hand_type, _)| std::cmp::Reverse(*hand_type))
+        .collect()
+}
+
+/// One line per hand, then how many hands of each type and how many were upgraded.
+pub fn report(standings: &[Standing]) -> String {
+    let mut lines = standings
+        .iter()
+        .map(|s| {
+            let upgrade = s
+                .upgraded_from
+                .map(|from| format!(" (from {:?})", from))
+                .unwrap_or_default();
+            format!(
+                "{:>5} {} {:<9} bid {:>4} wins {:>7}{}",
+                s.rank,
+                s.hand.value,
+                format!("{:?}", s.hand.hand_type),
+                s.hand.bid,
+                s.winnings,
+                upgrade
+            )
+        })
+        .collect::<Vec<_>>();
+    lines.push(String::new());
+    for (hand_type, count) in breakdown(standings) {
+        lines.push(format!("{:<9} {}", format!("{:?}", hand_type), count));
+    }
+    lines.push(format!(
+        "Upgraded  {}",
+        standings
+            .iter()
+            .filter(|s| s.upgraded_from.is_some())
+            .count()
+    ));
+    lines.push(format!(
+        "Total     {}",
+        standings.iter().map(|s| s.winnings).sum::<u32>()
+    ));
+    lines.join("\n")
+}
+
+pub fn to_csv(standings: &[Standing]) -> String {
+    let mut lines = vec!["rank,hand,type,bid,winnings,upgraded_from".to_owned()];
+    lines.extend(standings.iter().map(|s| {
+        format!(
+            "{},{},{:?},{},{},{}",
+            s.rank,
+            s.hand.value,
+            s.hand.hand_type,
+            s.hand.bid,
+            s.winnings,
+            s.upgraded_from
+                .map(|from| format!("{:?}", from))
+                .unwrap_or_default()
+        )
+    }));
+    lines.join("\n")
+}
+
+/// The standings along with the breakdown by hand type.
+pub fn to_json(standings: &[Standing]) -> String {
+    let hands = standings
+        .iter()
+        .map(|s| {
+            format!(
+                "{{\"rank\":{},\"hand\":\"{}\",\"type\":\"{:?}\",\"bid\":{},\"winnings\":{},\"upgraded_from\":{}}}",
+                s.rank,
+                s.hand.value,
+                s.hand.hand_type,
+                s.hand.bid,
+                s.winnings,
+                s.upgraded_from
+                    .map(|from| format!("\"{:?}\"", from))
+                    .unwrap_or("null".to_owned())
+            )
+        })
+        .collect::<Vec<_>>();
+    let types = breakdown(standings)
+        .iter()
+        .map(|(hand_type, count)| format!("\"{:?}\":{}", hand_type, count))
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"hands\":[{}],\"types\":{{{}}}}}",
+        hands.join(","),
+        types.join(",")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(total_winnings(INPUT, &Ruleset::standard()), 6440);
+        assert_eq!(total_winnings(INPUT, &Ruleset::jokers_wild()), 5905);
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let rules = Ruleset::jokers_wild();
+        assert_eq!(rules.hand_type("JJJJJ"), Some(HandType::Five));
+        assert_eq!(rules.hand_type("KKJ22"), Some(HandType::Full));
+        assert_eq!(rules.hand_type("KJJ22"), Some(HandType::Four));
+        assert_eq!(rules.hand_type("2345J"), Some(HandType::OnePair));
+        let a = Hand::new("JKKK2", 0, &rules);
+        let b = Hand::new("QQQQ2", 0, &rules);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+
+        let rules = Ruleset {
+            wildcards: vec!['J', '2'],
+            card_order: "2J3456789TQKA".chars().collect(),
+            ..Ruleset::standard()
+        };
+        assert_eq!(rules.hand_type("2J345"), Some(HandType::Three));
+        assert_eq!(rules.hand_type("22JAA"), Some(HandType::Five));
+        assert_eq!(rules.hand_type("2KQJA"), Some(HandType::Three));
+    }
+
+    /// The joker upgrades part 2 used to hard-code.
+    fn upgrade_table(value: &str) -> Option<HandType> {
+        let jokers = value.chars().filter(|c| *c == 'J').count();
+        let hand_type = Ruleset::standard().table_hand_type(&value.chars().collect::<Vec<_>>())?;
+        match (hand_type, jokers) {
+            (hand_type, 0) => Some(hand_type),
+            (HandType::Five | HandType::Four | HandType::Full, _) => Some(HandType::Five),
+            (HandType::Three, _) => Some(HandType::Four),
+            (HandType::TwoPairs, 1) => Some(HandType::Full),
+            (HandType::TwoPairs, 2) => Some(HandType::Four),
+            (HandType::TwoPairs, _) => None,
+            (HandType::OnePair, _) => Some(HandType::Three),
+            (HandType::HighCard, _) => Some(HandType::OnePair),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_best_substitution() {
+        let rules = Ruleset::jokers_wild();
+        assert_eq!(
+            rules.best_substitution("JJJJJ"),
+            Some((HandType::Five, "AAAAA".to_owned()))
+        );
+        assert_eq!(
+            rules.best_substitution("KTJJT"),
+            Some((HandType::Four, "KTTTT".to_owned()))
+        );
+        assert_eq!(
+            rules.best_substitution("2345J"),
+            Some((HandType::OnePair, "23455".to_owned()))
+        );
+        assert_eq!(
+            rules.best_substitution("32T3K"),
+            Some((HandType::OnePair, "32T3K".to_owned()))
+        );
+        assert_eq!(rules.best_substitution("32T3"), None);
+
+        for cards in (0..5)
+            .map(|_| rules.card_order.iter())
+            .multi_cartesian_product()
+        {
+            let value = cards.into_iter().collect::<String>();
+            let (hand_type, hand) = rules.best_substitution(&value).unwrap();
+            assert_eq!(Some(hand_type), upgrade_table(&value), "{}", value);
+            assert!(!hand.contains('J'), "{}", value);
+            assert_eq!(
+                Ruleset::standard().hand_type(&hand),
+                Some(hand_type),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_poker() {
+        let rules = Ruleset::poker();
+        let hand = |value| Hand::new(value, 0, &rules);
+        assert_eq!(rules.hand_type("2H3D5S9CKD"), Some(HandType::HighCard));
+        assert_eq!(rules.hand_type("AH2C3D4S5H"), Some(HandType::Straight));
+        assert_eq!(rules.hand_type("3D6D7DTDQD"), Some(HandType::Flush));
+        assert_eq!(rules.hand_type("TSJSQSKSAS"), Some(HandType::StraightFlush));
+        assert_eq!(rules.hand_type("2H2D4C4D4S"), Some(HandType::Full));
+        assert_eq!(rules.hand_type("2H2D4C4D2H"), None);
+        assert_eq!(rules.hand_type("2H2D4C4D4X"), None);
+        assert_eq!(rules.hand_type("32T3K"), None);
+
+        // Winner of each deal, kickers breaking ties between hands of the same type.
+        for (a, b) in [
+            ("5H5C6S7SKD", "2C3S8S8DTD"),
+            ("2C5C7D8SQH", "5D8C9SJSAC"),
+            ("2D9CASAHAC", "3D6D7DTDQD"),
+            ("3D6D7HQDQS", "4D6S9HQHQC"),
+            ("3C3D3S9S9D", "2H2D4C4D4S"),
+            ("AH2C3D4S5H", "2C3D4S5H6D"),
+            ("KS2S3S4S5S", "AH2H3H4H5H"),
+        ] {
+            assert_eq!(hand(a).cmp(&hand(b)), Ordering::Less, "{} {}", a, b);
+            assert!(hand(a).key < hand(b).key, "{} {}", a, b);
+        }
+        assert_eq!(hand("2H3D5S9CKD").cmp(&hand("KS2D3H5C9C")), Ordering::Equal);
+
+        let input = "2C3S8S8DTD 1
+5H5C6S7SKD 2
+TSJSQSKSAS 3
+AH2C3D4S5H 4";
+        assert_eq!(total_winnings(input, &rules), 2 + 2 + 4 * 3 + 3 * 4);
+
+        // Tied hands share their rank whatever the order of the lines.
+        let input = "2H3D5S9CKD 1
+KS2D3H5C9C 100
+2C3S8S8DTD 10";
+        assert_eq!(total_winnings(input, &rules), 1 + 100 + 10 * 3);
+        let swapped = input.lines().rev().collect::<Vec<_>>().join("\n");
+        assert_eq!(total_winnings(&swapped, &rules), 1 + 100 + 10 * 3);
+    }
+
+    #[test]
+    fn test_standings() {
+        let standings = standings(INPUT, &Ruleset::jokers_wild());
+        let summary = standings
+            .iter()
+            .map(|s| (s.hand.value.as_str(), s.rank, s.winnings, s.upgraded_from))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("32T3K", 1, 765, None),
+                ("KK677", 2, 56, None),
+                ("T55J5", 3, 2052, Some(HandType::Three)),
+                ("QQQJA", 4, 1932, Some(HandType::Three)),
+                ("KTJJT", 5, 1100, Some(HandType::TwoPairs)),
+            ]
+        );
+        assert_eq!(
+            breakdown(&standings),
+            vec![
+                (HandType::Four, 3),
+                (HandType::TwoPairs, 1),
+                (HandType::OnePair, 1)
+            ]
+        );
+        assert!(report(&standings).ends_with("Upgraded  3\nTotal     5905"));
+        let csv = to_csv(&standings);
+        assert_eq!(csv.lines().count(), 6);
+        assert_eq!(csv.lines().nth(3), Some("3,T55J5,Four,684,2052,Three"));
+        let json = to_json(&standings);
+        assert!(json.starts_with(
+            "{\"hands\":[{\"rank\":1,\"hand\":\"32T3K\",\"type\":\"OnePair\",\"bid\":765,\"winnings\":765,\"upgraded_from\":null},"
+        ));
+        assert!(json.ends_with("\"types\":{\"Four\":3,\"TwoPairs\":1,\"OnePair\":1}}"));
+
+        let standings = super::standings(INPUT, &Ruleset::standard());
+        assert!(standings.iter().all(|s| s.upgraded_from.is_none()));
+
+        let input = "2C3S8S8DTD 10
+KS2D3H5C9C 100
+2H3D5S9CKD 1";
+        let standings = super::standings(input, &Ruleset::poker());
+        let ranks = standings
+            .iter()
+            .map(|s| (s.hand.value.as_str(), s.rank))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranks,
+            vec![("KS2D3H5C9C", 1), ("2H3D5S9CKD", 1), ("2C3S8S8DTD", 3)]
+        );
+    }
+
+    #[test]
+    fn test_hand_size() {
+        let rules = Ruleset {
+            hand_size: 3,
+            hand_types: vec![
+                (vec![3], HandType::Three),
+                (vec![2, 1], HandType::OnePair),
+                (vec![1, 1, 1], HandType::HighCard),
+            ],
+            ..Ruleset::standard()
+        };
+        assert_eq!(rules.hand_type("AAA"), Some(HandType::Three));
+        assert_eq!(rules.hand_type("A2A"), Some(HandType::OnePair));
+        assert_eq!(rules.hand_type("A2AKK"), None);
+        let input = "AKQ 1
+22K 2
+KK2 3
+333 4";
+        assert_eq!(total_winnings(input, &rules), 1 + 2 * 2 + 3 * 3 + 4 * 4);
+    }
+
+    /// Every hand of five cards, as ordered by `Ord`, must have strictly increasing keys.
+    fn check_keys(rules: &Ruleset) {
+        let mut hands = (0..5)
+            .map(|_| rules.card_order.iter())
+            .multi_cartesian_product()
+            .map(|cards| Hand::new(&cards.into_iter().collect::<String>(), 0, rules))
+            .collect::<Vec<_>>();
+        assert_eq!(hands.len(), 13_usize.pow(5));
+        hands.sort();
+        for w in hands.windows(2) {
+            assert_eq!(w[0].cmp(&w[1]), Ordering::Less);
+            assert!(w[0].key < w[1].key, "{} {}", w[0].value, w[1].value);
+        }
+    }
+
+    #[test]
+    fn test_keys_standard() {
+        check_keys(&Ruleset::standard());
+    }
+
+    #[test]
+    fn test_keys_jokers_wild() {
+        check_keys(&Ruleset::jokers_wild());
+    }
+}
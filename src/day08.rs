@@ -0,0 +1,793 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use itertools::Itertools;
+use nom::bytes::complete::tag;
+use nom::character::complete::alphanumeric1;
+use nom::sequence::tuple;
+use nom::IResult;
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Left,
+    Right,
+}
+
+#[derive(Debug)]
+pub struct Node {
+    pub name: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl Node {
+    pub fn new(name: &str, left: &str, right: &str) -> Self {
+        Node {
+            name: name.to_owned(),
+            left: left.to_owned(),
+            right: right.to_owned(),
+        }
+    }
+
+    pub fn get_next(&self, instruction: &Instruction) -> &str {
+        match instruction {
+            Instruction::Left => &self.left,
+            Instruction::Right => &self.right,
+        }
+    }
+}
+
+pub fn parse_instructions(line: &str) -> Result<Vec<Instruction>, NetworkError> {
+    if line.is_empty() {
+        return Err(NetworkError::NoInstructions);
+    }
+    line.chars()
+        .map(|c| match c {
+            'L' => Ok(Instruction::Left),
+            'R' => Ok(Instruction::Right),
+            _ => Err(NetworkError::InvalidInstruction(c)),
+        })
+        .collect()
+}
+
+pub fn parse_node(line: &str) -> IResult<&str, Node> {
+    let (i, (name, _, left, _, right)) = tuple((
+        alphanumeric1,
+        tag(" = ("),
+        alphanumeric1,
+        tag(", "),
+        alphanumeric1,
+    ))(line)?;
+    Ok((i, Node::new(name, left, right)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    /// The instruction line is missing or empty, so walks could not take a single step.
+    NoInstructions,
+    InvalidInstruction(char),
+    InvalidLine(String),
+    DuplicateNode(String),
+    UnknownNode {
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::NoInstructions => write!(f, "No instructions on the first line"),
+            NetworkError::InvalidInstruction(c) => write!(f, "Unexpected instruction {}", c),
+            NetworkError::InvalidLine(line) => write!(f, "Could not parse node {}", line),
+            NetworkError::DuplicateNode(name) => write!(f, "Node {} is defined twice", name),
+            NetworkError::UnknownNode { from, to } => {
+                write!(f, "Node {} leads to unknown node {}", from, to)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Network {
+    pub instructions: Vec<Instruction>,
+    pub nodes: BTreeMap<String, Node>,
+}
+
+impl Network {
+    /// Instructions and nodes, checking that each node is defined once and only leads to
+    /// defined nodes.
+    pub fn parse(input: &str) -> Result<Self, NetworkError> {
+        let mut lines = input.lines();
+        let instructions = parse_instructions(lines.next().unwrap_or_default())?;
+        lines.next();
+
+        let mut nodes = BTreeMap::new();
+        for line in lines {
+            let (_, node) =
+                parse_node(line).map_err(|_| NetworkError::InvalidLine(line.to_owned()))?;
+            if nodes.contains_key(&node.name) {
+                return Err(NetworkError::DuplicateNode(node.name));
+            }
+            nodes.insert(node.name.to_owned(), node);
+        }
+        for node in nodes.values() {
+            if let Some(to) = [&node.left, &node.right]
+                .into_iter()
+                .find(|next| !nodes.contains_key(*next))
+            {
+                return Err(NetworkError::UnknownNode {
+                    from: node.name.to_owned(),
+                    to: to.to_owned(),
+                });
+            }
+        }
+        Ok(Network {
+            instructions,
+            nodes,
+        })
+    }
+
+    /// Follows the instructions from `start` until the same node is reached at the same
+    /// instruction, after which the walk can only repeat itself.
+    pub fn cycle(&self, start: &str, is_goal: impl Fn(&str) -> bool) -> Cycle {
+        let mut seen = HashMap::new();
+        let mut goals = Vec::new();
+        let mut node = self.nodes.get(start).expect("Could not find starting node");
+        let mut step = 0;
+        loop {
+            let index = step as usize % self.instructions.len();
+            if let Some(first) = seen.insert((node.name.as_str(), index), step) {
+                let (tail_hits, offsets): (Vec<_>, Vec<_>) =
+                    goals.iter().partition(|goal| **goal < first);
+                return Cycle {
+                    start: start.to_owned(),
+                    tail: first,
+                    length: step - first,
+                    tail_hits,
+                    offsets: offsets.iter().map(|goal| goal - first).collect(),
+                };
+            }
+            if is_goal(&node.name) {
+                goals.push(step);
+            }
+            let next_name = node.get_next(&self.instructions[index]);
+            node = self.nodes.get(next_name).expect("Unknown node");
+            step += 1;
+        }
+    }
+}
+
+/// Index of a node in a [`Graph`].
+pub type NodeId = u32;
+
+/// Network compiled to a dense list of nodes, walked without any lookup by name.
+#[derive(Debug)]
+pub struct Graph {
+    /// Sorted names, the index of each one being its node.
+    pub names: Vec<String>,
+    /// Nodes reached going left and right from each node.
+    pub edges: Vec<[NodeId; 2]>,
+    pub instructions: Vec<Instruction>,
+    /// Node reached from each node after following all the instructions once.
+    pub jumps: Vec<NodeId>,
+}
+
+impl Graph {
+    pub fn new(network: &Network) -> Self {
+        assert!(
+            network.nodes.len() <= NodeId::MAX as usize,
+            "Too many nodes to index"
+        );
+        let names = network.nodes.keys().cloned().collect::<Vec<_>>();
+        let mut graph = Graph {
+            names,
+            edges: Vec::new(),
+            instructions: network.instructions.clone(),
+            jumps: Vec::new(),
+        };
+        graph.edges = network
+            .nodes
+            .values()
+            .map(|node| {
+                [&node.left, &node.right].map(|name| graph.index(name).expect("Unknown node"))
+            })
+            .collect();
+        graph.jumps = (0..graph.names.len() as NodeId)
+            .map(|node| {
+                graph
+                    .instructions
+                    .iter()
+                    .fold(node, |node, instruction| graph.next(node, instruction))
+            })
+            .collect();
+        graph
+    }
+
+    /// Loop of the walk from each node matching `start`.
+    pub fn cycles(&self, start: &NodePattern, goal: &NodePattern) -> Vec<Cycle> {
+        (0..self.names.len() as NodeId)
+            .filter(|node| start.matches(&self.names[*node as usize]))
+            .map(|node| self.cycle(node, |name| goal.matches(name)))
+            .collect()
+    }
+
+    pub fn index(&self, name: &str) -> Option<NodeId> {
+        self.names
+            .binary_search_by(|n| n.as_str().cmp(name))
+            .ok()
+            .map(|i| i as NodeId)
+    }
+
+    pub fn next(&self, node: NodeId, instruction: &Instruction) -> NodeId {
+        match instruction {
+            Instruction::Left => self.edges[node as usize][0],
+            Instruction::Right => self.edges[node as usize][1],
+        }
+    }
+
+    /// Same as [`Network::cycle`], with the loop found by jumping over whole turns of
+    /// instructions first.
+    pub fn cycle(&self, start: NodeId, is_goal: impl Fn(&str) -> bool) -> Cycle {
+        let n = self.instructions.len() as u64;
+
+        // Nodes at the start of each turn until one comes back, the walk then repeats
+        // whole turns.
+        let mut turns = vec![None; self.names.len()];
+        let mut boundaries = Vec::new();
+        let mut node = start;
+        while turns[node as usize].is_none() {
+            turns[node as usize] = Some(boundaries.len());
+            boundaries.push(node);
+            node = self.jumps[node as usize];
+        }
+        let first_turn = turns[node as usize].expect("Node seen at no turn");
+        let length = (boundaries.len() - first_turn) as u64 * n;
+
+        // The loop may start during the turn before, where the walk and its next turn
+        // around the loop meet.
+        let mut tail = first_turn as u64 * n;
+        if first_turn > 0 {
+            let mut a = boundaries[first_turn - 1];
+            let mut b = boundaries[boundaries.len() - 1];
+            for (step, instruction) in self.instructions.iter().enumerate() {
+                if a == b {
+                    tail = (first_turn as u64 - 1) * n + step as u64;
+                    break;
+                }
+                a = self.next(a, instruction);
+                b = self.next(b, instruction);
+            }
+        }
+
+        let goals = self
+            .names
+            .iter()
+            .map(|name| is_goal(name))
+            .collect::<Vec<_>>();
+        let mut tail_hits = Vec::new();
+        let mut offsets = Vec::new();
+        let mut node = start;
+        for step in 0..tail + length {
+            if goals[node as usize] {
+                if step < tail {
+                    tail_hits.push(step);
+                } else {
+                    offsets.push(step - tail);
+                }
+            }
+            node = self.next(node, &self.instructions[(step % n) as usize]);
+        }
+        Cycle {
+            start: self.names[start as usize].to_owned(),
+            tail,
+            length,
+            tail_hits,
+            offsets,
+        }
+    }
+}
+
+/// Walk of a single ghost: `tail` steps to reach a loop it then follows forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: String,
+    pub tail: u64,
+    pub length: u64,
+    /// Steps ending on a goal before entering the loop.
+    pub tail_hits: Vec<u64>,
+    /// Steps ending on a goal during one turn of the loop, counted from its first step.
+    pub offsets: Vec<u64>,
+}
+
+impl Cycle {
+    pub fn is_goal_at(&self, step: u64) -> bool {
+        if step < self.tail {
+            self.tail_hits.contains(&step)
+        } else {
+            self.offsets
+                .binary_search(&((step - self.tail) % self.length))
+                .is_ok()
+        }
+    }
+}
+
+/// Which node names a walk starts from or ends on.
+#[derive(Debug, Clone)]
+pub enum NodePattern {
+    Exact(String),
+    Suffix(String),
+    Regex(Regex),
+}
+
+impl NodePattern {
+    /// `exact:AAA`, `suffix:Z` or `regex:^[AB]+Z$`, a name alone being exact.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            Some(("exact", name)) => Ok(NodePattern::Exact(name.to_owned())),
+            Some(("suffix", suffix)) => Ok(NodePattern::Suffix(suffix.to_owned())),
+            Some(("regex", regex)) => Regex::new(regex)
+                .map(NodePattern::Regex)
+                .map_err(|e| format!("Invalid regex {}: {}", regex, e)),
+            Some((kind, _)) => Err(format!("Unknown pattern kind {}", kind)),
+            None => Ok(NodePattern::Exact(spec.to_owned())),
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NodePattern::Exact(exact) => name == exact,
+            NodePattern::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            NodePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl fmt::Display for NodePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodePattern::Exact(name) => write!(f, "exact:{}", name),
+            NodePattern::Suffix(suffix) => write!(f, "suffix:{}", suffix),
+            NodePattern::Regex(regex) => write!(f, "regex:{}", regex),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NavigationError {
+    NoStart(String),
+    /// Starting nodes from which no goal can ever be reached.
+    Unreachable(Vec<String>),
+    /// Every start reaches a goal but never all at the same step, at least within a u64 of steps.
+    NeverTogether,
+}
+
+impl fmt::Display for NavigationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NavigationError::NoStart(pattern) => write!(f, "No node matches {}", pattern),
+            NavigationError::Unreachable(starts) => {
+                write!(f, "No goal can be reached from {}", starts.join(", "))
+            }
+            NavigationError::NeverTogether => {
+                write!(f, "Goals are reached but never all at the same step")
+            }
+        }
+    }
+}
+
+/// Steps until a walk from every node matching `start` stands on a `goal` node at once.
+pub fn navigate(
+    graph: &Graph,
+    start: &NodePattern,
+    goal: &NodePattern,
+) -> Result<u64, NavigationError> {
+    let cycles = graph.cycles(start, goal);
+    if cycles.is_empty() {
+        return Err(NavigationError::NoStart(start.to_string()));
+    }
+    let unreachable = cycles
+        .iter()
+        .filter(|c| c.offsets.is_empty() && c.tail_hits.iter().all(|step| *step == 0))
+        .map(|c| c.start.to_owned())
+        .collect::<Vec<_>>();
+    if !unreachable.is_empty() {
+        return Err(NavigationError::Unreachable(unreachable));
+    }
+    all_goals(&cycles).ok_or(NavigationError::NeverTogether)
+}
+
+const GHOST_COLORS: [&str; 6] = ["red", "blue", "darkgreen", "orange", "purple", "brown"];
+
+/// Graphviz graph of the network with start and goal nodes filled, and the edges each ghost
+/// keeps following once in its loop drawn in its own colour.
+pub fn to_dot(graph: &Graph, start: &NodePattern, goal: &NodePattern) -> String {
+    let n = graph.instructions.len() as u64;
+    let mut colors = HashMap::new();
+    let starts = (0..graph.names.len() as NodeId)
+        .filter(|node| start.matches(&graph.names[*node as usize]))
+        .collect::<Vec<_>>();
+    for (ghost, node) in starts.iter().enumerate() {
+        let cycle = graph.cycle(*node, |name| goal.matches(name));
+        let mut node = *node;
+        for step in 0..cycle.tail + cycle.length {
+            let instruction = &graph.instructions[(step % n) as usize];
+            let next = graph.next(node, instruction);
+            if step >= cycle.tail {
+                colors
+                    .entry((node, next))
+                    .or_insert(GHOST_COLORS[ghost % GHOST_COLORS.len()]);
+            }
+            node = next;
+        }
+    }
+
+    let mut res = String::from("digraph network {\n    node [shape=circle];\n");
+    for (node, name) in graph.names.iter().enumerate() {
+        let fill = match (start.matches(name), goal.matches(name)) {
+            (true, true) => " style=filled fillcolor=gold",
+            (true, false) => " style=filled fillcolor=palegreen",
+            (false, true) => " style=filled fillcolor=lightcoral",
+            (false, false) => "",
+        };
+        res.push_str(&format!("    n{} [label=\"{}\"{}];\n", node, name, fill));
+    }
+    for (node, [left, right]) in graph.edges.iter().enumerate() {
+        let node = node as NodeId;
+        let edges = if left == right {
+            vec![(left, "LR")]
+        } else {
+            vec![(left, "L"), (right, "R")]
+        };
+        for (next, label) in edges {
+            let color = colors
+                .get(&(node, *next))
+                .map(|color| format!(" color={} penwidth=2", color))
+                .unwrap_or_default();
+            res.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"{}];\n",
+                node, next, label, color
+            ));
+        }
+    }
+    res.push_str("}\n");
+    res
+}
+
+/// Smallest positive number of steps after which every ghost stands on a goal at once. `None`
+/// when that never happens or does not fit in a u64.
+pub fn all_goals(cycles: &[Cycle]) -> Option<u64> {
+    let tail = cycles.iter().map(|c| c.tail).max()?;
+    if let Some(step) = (1..=tail).find(|step| cycles.iter().all(|c| c.is_goal_at(*step))) {
+        return Some(step);
+    }
+
+    // Past the longest tail every ghost is in its loop. Stepping through the goals of one ghost
+    // only pays off when the loops come back together sooner than there are combinations of
+    // goal offsets to solve.
+    let combinations = cycles
+        .iter()
+        .map(|c| c.offsets.len() as u64)
+        .fold(1_u64, |acc, n| acc.saturating_mul(n));
+    if simulation_steps(cycles).is_some_and(|steps| steps < combinations) {
+        return simulate(cycles, tail);
+    }
+    let mut congruences = vec![(0_i128, 1_i128)];
+    for c in cycles {
+        congruences = congruences
+            .iter()
+            .cartesian_product(c.offsets.iter())
+            .filter_map(|(congruence, o)| {
+                crt(*congruence, ((c.tail + o) as i128, c.length as i128))
+            })
+            .collect();
+        // They all share the same modulus, so equal remainders are the same steps.
+        congruences.sort_unstable();
+        congruences.dedup();
+    }
+    let from = tail as i128 + 1;
+    congruences
+        .iter()
+        .filter_map(|(remainder, modulus)| {
+            from.checked_add((remainder - from).rem_euclid(*modulus))
+        })
+        .min()
+        .and_then(|step| u64::try_from(step).ok())
+}
+
+/// Solution of both `x = a1 mod m1` and `x = a2 mod m2`, as a remainder and modulus. The moduli
+/// do not have to be coprime. `None` when there is no solution or when it does not fit in an
+/// i128.
+fn crt((a1, m1): (i128, i128), (a2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let (g, inverse, _) = extended_gcd(m1, m2);
+    if (a2 - a1) % g != 0 {
+        return None;
+    }
+    let modulus = (m1 / g).checked_mul(m2)?;
+    let k = ((a2 - a1) / g % (m2 / g)).checked_mul(inverse)? % (m2 / g);
+    let remainder = a1.checked_add(m1.checked_mul(k)?)?;
+    Some((remainder.rem_euclid(modulus), modulus))
+}
+
+/// `(g, x, y)` with `g = gcd(a, b) = a * x + b * y`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+    let (g, x, y) = extended_gcd(b, a % b);
+    (g, y, x - a / b * y)
+}
+
+/// Ghost reaching its goals least often.
+fn pilot(cycles: &[Cycle]) -> Option<&Cycle> {
+    cycles.iter().min_by(|a, b| {
+        (a.offsets.len() as u128 * b.length as u128)
+            .cmp(&(b.offsets.len() as u128 * a.length as u128))
+    })
+}
+
+/// Steps after which all the loops have come back to where they started together.
+fn period(cycles: &[Cycle]) -> Option<u64> {
+    cycles.iter().try_fold(1_u64, |acc, c| {
+        (acc / gcd_of_two_numbers(acc, c.length)).checked_mul(c.length)
+    })
+}
+
+/// Goals of the pilot that `simulate` may have to check.
+fn simulation_steps(cycles: &[Cycle]) -> Option<u64> {
+    let pilot = pilot(cycles)?;
+    (period(cycles)? / pilot.length).checked_mul(pilot.offsets.len() as u64)
+}
+
+/// Goes through the goals of the pilot, until all the loops have come back to where they
+/// started together.
+fn simulate(cycles: &[Cycle], tail: u64) -> Option<u64> {
+    let pilot = pilot(cycles)?;
+    let last = tail.saturating_add(period(cycles).unwrap_or(u64::MAX));
+    (0..)
+        .flat_map(|turn| {
+            pilot
+                .offsets
+                .iter()
+                .map(move |o| pilot.tail + o + turn * pilot.length)
+        })
+        .skip_while(|step| *step <= tail)
+        .take_while(|step| *step <= last)
+        .find(|step| cycles.iter().all(|c| c.is_goal_at(*step)))
+}
+
+fn gcd_of_two_numbers(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        return a;
+    }
+    gcd_of_two_numbers(b, a % b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = "LR
+
+AAA = (AAB, XXX)
+AAB = (XXX, AAZ)
+AAZ = (AAB, XXX)
+BBA = (BBB, XXX)
+BBB = (BBC, BBC)
+BBC = (BBZ, BBZ)
+BBZ = (BBB, BBB)
+XXX = (XXX, XXX)";
+
+    #[test]
+    fn test_cycle() {
+        let network = Network::parse(INPUT).unwrap();
+        let cycle = network.cycle("AAA", |name| name.ends_with('Z'));
+        assert_eq!((cycle.tail, cycle.length), (1, 2));
+        assert_eq!(cycle.offsets, vec![1]);
+        let cycle = network.cycle("BBA", |name| name.ends_with('Z'));
+        assert_eq!((cycle.tail, cycle.length), (1, 6));
+        assert_eq!(cycle.offsets, vec![2, 5]);
+        assert_eq!(all_goals(&[cycle]), Some(3));
+    }
+
+    #[test]
+    fn test_parse() {
+        let network = Network::parse(
+            "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+XXX = (XXX, XXX)",
+        )
+        .unwrap();
+        assert_eq!(network.nodes["11B"].right, "11Z");
+        assert_eq!(
+            Network::parse("L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)").unwrap_err(),
+            NetworkError::DuplicateNode("AAA".to_owned())
+        );
+        assert_eq!(
+            Network::parse("L\n\nAAA = (AAA, BBB)").unwrap_err(),
+            NetworkError::UnknownNode {
+                from: "AAA".to_owned(),
+                to: "BBB".to_owned()
+            }
+        );
+        assert_eq!(
+            Network::parse("L\n\nA-A = (AAA, AAA)").unwrap_err(),
+            NetworkError::InvalidLine("A-A = (AAA, AAA)".to_owned())
+        );
+        for input in ["", "\n\nAAA = (AAA, AAA)"] {
+            assert_eq!(
+                Network::parse(input).unwrap_err(),
+                NetworkError::NoInstructions
+            );
+        }
+        assert_eq!(
+            Network::parse("LRX\n\nAAA = (AAA, AAA)").unwrap_err(),
+            NetworkError::InvalidInstruction('X')
+        );
+    }
+
+    fn check_graph(input: &str) {
+        let network = Network::parse(input).unwrap();
+        let graph = Graph::new(&network);
+        for name in network.nodes.keys() {
+            let node = graph.index(name).unwrap();
+            assert_eq!(&graph.names[node as usize], name);
+            assert_eq!(
+                graph.cycle(node, |name| name.ends_with('Z')),
+                network.cycle(name, |name| name.ends_with('Z'))
+            );
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        check_graph(INPUT);
+        check_graph(
+            "RRLRL
+
+AAA = (BBB, CCC)
+BBB = (CCC, DDD)
+CCC = (DDD, BBZ)
+DDD = (BBB, EEE)
+EEE = (AAA, BBZ)
+BBZ = (BBZ, CCC)",
+        );
+        let network = Network::parse(include_str!("bin/inputs/day08")).unwrap();
+        let graph = Graph::new(&network);
+        for name in network.nodes.keys().filter(|name| name.ends_with('A')) {
+            assert_eq!(
+                graph.cycle(graph.index(name).unwrap(), |name| name.ends_with('Z')),
+                network.cycle(name, |name| name.ends_with('Z'))
+            );
+        }
+    }
+
+    #[test]
+    fn test_navigate() {
+        let graph = Graph::new(&Network::parse(INPUT).unwrap());
+        let pattern = |spec| NodePattern::parse(spec).unwrap();
+        assert_eq!(navigate(&graph, &pattern("AAA"), &pattern("AAZ")), Ok(2));
+        assert_eq!(
+            navigate(&graph, &pattern("suffix:A"), &pattern("suffix:Z")),
+            Ok(6)
+        );
+        assert_eq!(
+            navigate(&graph, &pattern("regex:^[AB]+A$"), &pattern("exact:BBZ")),
+            Err(NavigationError::Unreachable(vec!["AAA".to_owned()]))
+        );
+        assert_eq!(
+            navigate(&graph, &pattern("BBA"), &pattern("regex:B[BC]")),
+            Ok(1)
+        );
+        assert_eq!(navigate(&graph, &pattern("XXX"), &pattern("XXX")), Ok(1));
+        assert_eq!(
+            navigate(&graph, &pattern("CCA"), &pattern("XXX")),
+            Err(NavigationError::NoStart("exact:CCA".to_owned()))
+        );
+        assert_eq!(navigate(&graph, &pattern("AAZ"), &pattern("AAZ")), Ok(2));
+        assert!(NodePattern::parse("regex:(").is_err());
+        assert!(NodePattern::parse("prefix:A").is_err());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = Graph::new(&Network::parse(INPUT).unwrap());
+        let dot = to_dot(
+            &graph,
+            &NodePattern::parse("suffix:A").unwrap(),
+            &NodePattern::parse("suffix:Z").unwrap(),
+        );
+        let lines = dot.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "digraph network {");
+        assert!(lines.contains(&"    n0 [label=\"AAA\" style=filled fillcolor=palegreen];"));
+        assert!(lines.contains(&"    n2 [label=\"AAZ\" style=filled fillcolor=lightcoral];"));
+        assert!(lines.contains(&"    n1 [label=\"AAB\"];"));
+        // AAA only goes through AAB once, then loops between AAB and AAZ.
+        assert!(lines.contains(&"    n0 -> n1 [label=\"L\"];"));
+        assert!(lines.contains(&"    n1 -> n2 [label=\"R\" color=red penwidth=2];"));
+        assert!(lines.contains(&"    n2 -> n1 [label=\"L\" color=red penwidth=2];"));
+        assert!(lines.contains(&"    n5 -> n6 [label=\"LR\" color=blue penwidth=2];"));
+        assert!(lines.contains(&"    n7 -> n7 [label=\"LR\"];"));
+        assert_eq!(lines.iter().filter(|l| l.contains("->")).count(), 12);
+    }
+
+    fn ghost(tail: u64, length: u64, tail_hits: Vec<u64>, offsets: Vec<u64>) -> Cycle {
+        Cycle {
+            start: String::new(),
+            tail,
+            length,
+            tail_hits,
+            offsets,
+        }
+    }
+
+    #[test]
+    fn test_all_goals() {
+        // Goals that are not at multiples of the first one, so the plain LCM would be wrong.
+        let cycles = vec![ghost(2, 5, vec![1], vec![1]), ghost(0, 4, vec![], vec![2])];
+        assert_eq!(all_goals(&cycles), Some(18));
+        assert_eq!(simulate(&cycles, 2), Some(18));
+
+        // Met during the tails.
+        let cycles = vec![ghost(3, 5, vec![1], vec![1]), ghost(0, 4, vec![], vec![1])];
+        assert_eq!(all_goals(&cycles), Some(1));
+
+        // Parities never agree.
+        let cycles = vec![
+            ghost(0, 2, vec![], vec![0]),
+            ghost(0, 4, vec![], vec![1, 3]),
+        ];
+        assert_eq!(all_goals(&cycles), None);
+        assert_eq!(simulate(&cycles, 0), None);
+
+        let cycles = vec![
+            ghost(3, 7, vec![], vec![1, 4]),
+            ghost(5, 6, vec![2], vec![0, 3, 5]),
+            ghost(1, 10, vec![], vec![9]),
+        ];
+        let expected = (1..1000).find(|step| cycles.iter().all(|c| c.is_goal_at(*step)));
+        assert!(expected.is_some());
+        assert_eq!(all_goals(&cycles), expected);
+        assert_eq!(simulate(&cycles, 5), expected);
+        // Many offsets on short loops, cheaper to simulate.
+        let cycles = vec![
+            ghost(0, 6, vec![], vec![1, 2, 3, 4, 5]),
+            ghost(0, 4, vec![], vec![1, 3]),
+            ghost(2, 9, vec![], vec![0, 1, 2, 3, 4, 5, 7]),
+        ];
+        assert!(simulation_steps(&cycles) < Some(5 * 2 * 7));
+        let expected = (1..1000).find(|step| cycles.iter().all(|c| c.is_goal_at(*step)));
+        assert_eq!(all_goals(&cycles), expected);
+
+        // Long loops with many offsets, far too long to simulate.
+        let cycles = [10007, 10009, 10037, 10039, 10061, 10067]
+            .iter()
+            .zip(0..)
+            .map(|(length, i)| {
+                let offsets = vec![
+                    11 * i + 1,
+                    97 * i + 5,
+                    1000 + 13 * i,
+                    4000 + i,
+                    9000 - 3 * i,
+                ];
+                ghost(0, *length, vec![], offsets)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(simulation_steps(&cycles), None);
+        assert_eq!(all_goals(&cycles), Some(4774965665758392210));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((0, 4), (1, 6)), None);
+        let big = 1_i128 << 100;
+        assert_eq!(crt((0, big), (1, big + 1)), None);
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, space0};
+use nom::multi::{many0, many1};
+use nom::sequence::tuple;
+use nom::IResult;
+
+pub fn parse_history(line: &str) -> IResult<&str, Vec<i64>> {
+    let (i, values) = many1(tuple((space0, many0(tag("-")), digit1, space0)))(line)?;
+    let values = values
+        .iter()
+        .map(|(_, sign, value, _)| {
+            if sign.is_empty() {
+                value.parse::<i64>().expect("Could not parse i64")
+            } else {
+                -value.parse::<i64>().expect("Could not parse i64")
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok((i, values))
+}
+
+/// A difference or a prediction that does not fit in an i64, or a step towards it that does
+/// not fit in an i128.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Prediction does not fit in i64")
+    }
+}
+
+/// `sum (-1)^(k - j) C(k, j) f(j)`, building each binomial coefficient from the previous one.
+fn difference(history: &[i64], k: usize) -> Result<i128, Overflow> {
+    let mut binomial = 1_i128;
+    let mut res = 0_i128;
+    for (j, value) in history.iter().take(k + 1).enumerate() {
+        let term = binomial.checked_mul(*value as i128).ok_or(Overflow)?;
+        res = if (k - j).is_multiple_of(2) {
+            res.checked_add(term)
+        } else {
+            res.checked_sub(term)
+        }
+        .ok_or(Overflow)?;
+        let j = j as i128;
+        binomial = binomial.checked_mul(k as i128 - j).ok_or(Overflow)? / (j + 1);
+    }
+    Ok(res)
+}
+
+/// First value of the `k`-th row of differences, without building the rows above it.
+pub fn forward_difference(history: &[i64], k: usize) -> Result<i64, Overflow> {
+    difference(history, k)?.try_into().map_err(|_| Overflow)
+}
+
+/// Degree of the polynomial going through every value of the history, `None` when they are
+/// all zero.
+pub fn degree(history: &[i64]) -> Result<Option<usize>, Overflow> {
+    for k in (0..history.len()).rev() {
+        if difference(history, k)? != 0 {
+            return Ok(Some(k));
+        }
+    }
+    Ok(None)
+}
+
+/// Value of that polynomial at `index`, counted from the first value of the history so that
+/// the next value is at `history.len()` and the previous one at -1.
+pub fn predict(history: &[i64], index: i64) -> Result<i64, Overflow> {
+    assert!(!history.is_empty(), "Cannot predict from an empty history");
+    newton(history, degree(history)?, index)
+}
+
+/// Newton form `sum C(index, j) forward_difference(history, j)`, up to the degree of the
+/// polynomial so that far indexes do not go through the higher, zero, terms.
+fn newton(history: &[i64], degree: Option<usize>, index: i64) -> Result<i64, Overflow> {
+    let Some(degree) = degree else {
+        return Ok(0);
+    };
+    let x = index as i128;
+    let mut binomial = 1_i128;
+    let mut res = 0_i128;
+    for j in 0..=degree {
+        let term = binomial
+            .checked_mul(difference(history, j)?)
+            .ok_or(Overflow)?;
+        res = res.checked_add(term).ok_or(Overflow)?;
+        if j < degree {
+            let j = j as i128;
+            binomial = binomial.checked_mul(x - j).ok_or(Overflow)? / (j + 1);
+        }
+    }
+    res.try_into().map_err(|_| Overflow)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryError {
+    Empty,
+    /// The differences run out before reaching a row of zeros, so any polynomial of a higher
+    /// degree would fit the history as well.
+    NotPolynomial {
+        values: usize,
+    },
+    Overflow,
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Empty => write!(f, "History is empty"),
+            HistoryError::NotPolynomial { values } => write!(
+                f,
+                "Differences of these {} values never reach a row of zeros",
+                values
+            ),
+            HistoryError::Overflow => write!(f, "{}", Overflow),
+        }
+    }
+}
+
+impl From<Overflow> for HistoryError {
+    fn from(_: Overflow) -> Self {
+        HistoryError::Overflow
+    }
+}
+
+/// Degree of the polynomial followed by the history, as long as its differences reach a row of
+/// zeros. `None` when the history itself is all zeros.
+pub fn fit(history: &[i64]) -> Result<Option<usize>, HistoryError> {
+    if history.is_empty() {
+        return Err(HistoryError::Empty);
+    }
+    match degree(history)? {
+        Some(degree) if degree + 1 == history.len() => Err(HistoryError::NotPolynomial {
+            values: history.len(),
+        }),
+        degree => Ok(degree),
+    }
+}
+
+/// The history then each row of differences, down to the first row of zeros.
+pub fn difference_table(history: &[i64]) -> Result<Vec<Vec<i64>>, HistoryError> {
+    fit(history)?;
+    let mut table = vec![history.to_vec()];
+    while let Some(row) = table.last().filter(|row| row.iter().any(|v| *v != 0)) {
+        let next = row
+            .windows(2)
+            .map(|w| w[1].checked_sub(w[0]).ok_or(HistoryError::Overflow))
+            .collect::<Result<_, _>>()?;
+        table.push(next);
+    }
+    Ok(table)
+}
+
+/// `k`-th value after the last one of the history.
+pub fn next(history: &[i64], k: u32) -> Result<i64, HistoryError> {
+    let degree = fit(history)?;
+    Ok(newton(
+        history,
+        degree,
+        history.len() as i64 - 1 + k as i64,
+    )?)
+}
+
+/// `k`-th value before the first one of the history.
+pub fn previous(history: &[i64], k: u32) -> Result<i64, HistoryError> {
+    let degree = fit(history)?;
+    Ok(newton(history, degree, -(k as i64))?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Next value from the rows of differences, as part 1 first did it.
+    fn extrapolate(history: &[i64]) -> i64 {
+        let mut row = history.to_vec();
+        let mut res = 0;
+        while row.iter().any(|v| *v != 0) {
+            res += row.last().unwrap();
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+        res
+    }
+
+    #[test]
+    fn test_predict() {
+        let histories = [
+            vec![0, 3, 6, 9, 12, 15],
+            vec![1, 3, 6, 10, 15, 21],
+            vec![10, 13, 16, 21, 30, 45],
+        ];
+        let next = histories
+            .iter()
+            .map(|h| predict(h, 6).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(next, vec![18, 28, 68]);
+        let previous = histories
+            .iter()
+            .map(|h| predict(h, -1).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(previous, vec![-3, 0, 5]);
+        let degrees = histories
+            .iter()
+            .map(|h| degree(h).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(degrees, vec![Some(1), Some(2), Some(3)]);
+
+        assert_eq!(predict(&[1, 3, 6, 10, 15, 21], 99), Ok(5050));
+        assert_eq!(predict(&[1, 3, 6, 10, 15, 21], 2), Ok(6));
+        assert_eq!(predict(&[7], -5), Ok(7));
+        assert_eq!(predict(&[0, 0], 9), Ok(0));
+        assert_eq!(degree(&[0, 0, 0]), Ok(None));
+        assert_eq!(degree(&[1, -1, 1, -1]), Ok(Some(3)));
+        assert_eq!(forward_difference(&[1, -1, 1, -1], 3), Ok(-8));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Only the terms up to the degree count, so far indexes stay exact.
+        let linear = (0..21).map(|x| 3 * x + 1).collect::<Vec<_>>();
+        assert_eq!(predict(&linear, 1020), Ok(3061));
+        assert_eq!(predict(&linear, -1_000_000_000), Ok(-2_999_999_999));
+        let cubic = [0, 1, 8, 27, 64];
+        assert_eq!(predict(&cubic, 2_000_000), Ok(8_000_000_000_000_000_000));
+        assert_eq!(predict(&cubic, 3_000_000), Err(Overflow));
+        assert_eq!(predict(&cubic, i64::MIN), Err(Overflow));
+
+        let extreme = [i64::MAX, i64::MIN, 0];
+        assert_eq!(forward_difference(&extreme, 1), Err(Overflow));
+        assert_eq!(forward_difference(&extreme, 2), Err(Overflow));
+        assert_eq!(predict(&extreme, 1), Ok(i64::MIN));
+        assert_eq!(predict(&extreme, 3), Err(Overflow));
+        // Binomial coefficients of the last rows no longer fit in an i128.
+        let alternating = (0..140)
+            .map(|i| if i % 2 == 0 { 1_000_000 } else { -1_000_000 })
+            .collect::<Vec<_>>();
+        assert_eq!(degree(&alternating), Err(Overflow));
+        assert_eq!(predict(&alternating, 140), Err(Overflow));
+    }
+
+    #[test]
+    fn test_difference_table() {
+        assert_eq!(
+            difference_table(&[1, 3, 6, 10, 15, 21]),
+            Ok(vec![
+                vec![1, 3, 6, 10, 15, 21],
+                vec![2, 3, 4, 5, 6],
+                vec![1, 1, 1, 1],
+                vec![0, 0, 0],
+            ])
+        );
+        assert_eq!(difference_table(&[0, 0]), Ok(vec![vec![0, 0]]));
+        assert_eq!(difference_table(&[4, 4]), Ok(vec![vec![4, 4], vec![0]]));
+        assert_eq!(
+            difference_table(&[1, -1, 1, -1]),
+            Err(HistoryError::NotPolynomial { values: 4 })
+        );
+        assert_eq!(difference_table(&[]), Err(HistoryError::Empty));
+    }
+
+    #[test]
+    fn test_next_previous() {
+        let history = [10, 13, 16, 21, 30, 45];
+        assert_eq!(next(&history, 1), Ok(68));
+        assert_eq!(next(&history, 2), Ok(101));
+        assert_eq!(next(&history, 0), Ok(45));
+        assert_eq!(previous(&history, 1), Ok(5));
+        assert_eq!(previous(&history, 2), Ok(-4));
+        assert_eq!(fit(&history), Ok(Some(3)));
+        // A single value could be followed by anything.
+        assert_eq!(
+            next(&[5], 1),
+            Err(HistoryError::NotPolynomial { values: 1 })
+        );
+        assert_eq!(next(&[0], 3), Ok(0));
+        assert_eq!(fit(&[0, 0, 0]), Ok(None));
+
+        let linear = (0..21).map(|x| 3 * x + 1).collect::<Vec<_>>();
+        assert_eq!(next(&linear, 1000), Ok(3061));
+        assert_eq!(previous(&linear, 1000), Ok(-2999));
+        let quintic = (0..7).map(|x| x * x * x * x * x).collect::<Vec<_>>();
+        assert_eq!(next(&quintic, 1000), Ok(1006_i64.pow(5)));
+        assert_eq!(next(&quintic, u32::MAX), Err(HistoryError::Overflow));
+        assert_eq!(previous(&quintic, u32::MAX), Err(HistoryError::Overflow));
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        let history = [i64::MAX, i64::MIN, 0];
+        assert_eq!(
+            next(&history, 1),
+            Err(HistoryError::NotPolynomial { values: 3 })
+        );
+        assert_eq!(
+            difference_table(&history),
+            Err(HistoryError::NotPolynomial { values: 3 })
+        );
+        // A parabola whose values fit but whose differences do not.
+        let history = [i64::MAX, i64::MAX, i64::MAX / 3, i64::MIN];
+        assert_eq!(fit(&history), Ok(Some(2)));
+        assert_eq!(difference_table(&history), Err(HistoryError::Overflow));
+        assert_eq!(next(&history, 1), Err(HistoryError::Overflow));
+        let history = [i64::MIN, -1, i64::MAX - 1];
+        assert_eq!(previous(&history, 1), Err(HistoryError::Overflow));
+        assert_eq!(next(&history, 0), Ok(i64::MAX - 1));
+        assert_eq!(next(&history, 1), Err(HistoryError::Overflow));
+        let alternating = (0..140)
+            .map(|i| if i % 2 == 0 { 1_000_000 } else { -1_000_000 })
+            .collect::<Vec<_>>();
+        assert_eq!(next(&alternating, 1), Err(HistoryError::Overflow));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_predict_matches_polynomial(
+            coefficients in prop::collection::vec(-1000_i64..1000, 1..6),
+            extra in 0_usize..5,
+            index in -30_i64..60,
+        ) {
+            let p = |x: i64| coefficients.iter().rev().fold(0, |acc, c| acc * x + c);
+            let history = (0..(coefficients.len() + extra) as i64).map(p).collect::<Vec<_>>();
+            prop_assert_eq!(predict(&history, index), Ok(p(index)));
+            let highest = coefficients.iter().rposition(|c| *c != 0);
+            prop_assert_eq!(degree(&history), Ok(highest));
+        }
+
+        #[test]
+        fn prop_predict_matches_differences(
+            history in prop::collection::vec(-1000_i64..1000, 1..12),
+        ) {
+            prop_assert_eq!(
+                predict(&history, history.len() as i64),
+                Ok(extrapolate(&history))
+            );
+        }
+    }
+}